@@ -4,15 +4,21 @@ use std::str::FromStr;
 use anyhow::{anyhow, Result};
 use bpaf::*;
 use dirs::{config_dir, home_dir};
-use crate::Input;
+use ssh_key::HashAlg;
+use crate::{Expiry, Input, KeyType, Trust};
+use crate::command::Format;
 
 #[derive(Clone, Debug)]
 pub enum Command {
     Init(Init),
+    Passwd(Passwd),
     Keys(Keys),
     Sign(Sign),
     Verify(Verify),
-    Compat,
+    Countersign(Countersign),
+    Contacts(Contacts),
+    Git(Git),
+    Compat(Compat, Vec<String>),
 }
 
 #[derive(Clone, Debug)]
@@ -20,64 +26,209 @@ pub struct Init {
     pub secret: bool,
 }
 
+#[derive(Clone, Debug)]
+pub struct Passwd {
+    pub secret: Option<bool>,
+}
+
 #[derive(Clone, Debug)]
 pub enum Keys {
-    Create,
+    Create(Create),
     Delete(String),
     Export(String),
-    Import(Input),
+    Import(Input, Describe),
+    Edit(String, Describe),
     Public(String),
+    Allow(Allow),
+    Signers(Vec<String>),
+    Expiry(Expiry),
+    Rotate(String),
+    Statement(String),
+    Revoke(Revoke),
+    Revocations(Revocations),
     List,
 }
 
+#[derive(Clone, Debug)]
+pub struct Create {
+    pub kind:     KeyType,
+    pub after:    Option<String>,
+    pub expires:  Option<String>,
+    pub describe: Describe,
+}
+
+#[derive(Clone, Debug)]
+pub struct Describe {
+    pub label:      Option<String>,
+    pub comment:    Option<String>,
+    pub principals: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Revoke {
+    pub key:    String,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Revocations {
+    pub keychains: Vec<String>,
+    pub krl:       Option<PathBuf>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Allow {
+    pub key:        String,
+    pub principals: String,
+    pub namespaces: Option<String>,
+    pub after:      Option<String>,
+    pub before:     Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Sign {
+    pub key:    String,
+    pub ns:     String,
+    pub hash:   HashAlg,
+    pub append: bool,
+    pub data:   Input,
+}
+
+#[derive(Clone, Debug)]
+pub struct Countersign {
     pub key:  String,
-    pub ns:   String,
-    pub data: Input,
+    pub hash: HashAlg,
+    pub sig:  PathBuf,
 }
 
 #[derive(Clone, Debug)]
 pub struct Verify {
-    pub sig:  PathBuf,
-    pub ns:   String,
-    pub data: Input,
+    pub sig:       PathBuf,
+    pub ns:        String,
+    pub revoked:   Option<PathBuf>,
+    pub threshold: Option<usize>,
+    pub signers:   Option<String>,
+    pub time:      Option<String>,
+    pub data:      Input,
+}
+
+#[derive(Clone, Debug)]
+pub enum Contacts {
+    Add(Add),
+    Import(Import),
+    Refresh,
+    Succession(Input),
+    Delete(String),
+    List,
+}
+
+#[derive(Clone, Debug)]
+pub struct Add {
+    pub key:        Input,
+    pub principals: Vec<String>,
+    pub note:       String,
+    pub trust:      Trust,
+}
+
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub source:     String,
+    pub principals: Vec<String>,
+    pub note:       String,
+    pub trust:      Trust,
+}
+
+#[derive(Clone, Debug)]
+pub enum Git {
+    Setup(Setup),
+    Verify(Commits),
+}
+
+#[derive(Clone, Debug)]
+pub struct Setup {
+    pub key:    Option<String>,
+    pub global: bool,
+    pub undo:   bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Commits {
+    pub range:   String,
+    pub first:   bool,
+    pub signers: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Compat {
+    Sign(Sign),
+    Find(Find),
+    Match(Match),
+    Verify(Allowed),
+    Check(Check),
+}
+
+#[derive(Clone, Debug)]
+pub struct Find {
+    pub signers: PathBuf,
+    pub sig:     PathBuf,
+}
+
+#[derive(Clone, Debug)]
+pub struct Match {
+    pub signers:  PathBuf,
+    pub identity: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Allowed {
+    pub signers:  PathBuf,
+    pub identity: String,
+    pub ns:       String,
+    pub sig:      PathBuf,
+    pub revoked:  Option<PathBuf>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub ns:  String,
+    pub sig: PathBuf,
 }
 
 #[derive(Debug)]
 pub struct Args {
     pub store:   PathBuf,
+    pub format:  Format,
     pub command: Command
 }
 
-pub fn args() -> Result<(PathBuf, Command)> {
+pub fn args() -> Result<(PathBuf, Format, Command)> {
     let Args {
         store,
+        format,
         command,
         ..
     } = parser().run();
-    Ok((store, command))
+    Ok((store, format, command))
 }
 
 pub fn parser() -> OptionParser<Args> {
     let store  = short('S').argument("DIR").hide();
     let store  = store.fallback_with(store_dir);
-
-    let sig    = short('s').long("signature").argument("FILE");
-    let ns     = short('n').long("namespace").argument("NAMESPACE");
-    let data   = input("FILE");
-    let verify = construct!(Verify { sig, ns, data });
-    let verify = construct!(Command::Verify(verify)).to_options();
+    let format = long("json").req_flag(Format::Json).fallback(Format::Text);
 
     let init   = init().command("init");
+    let passwd = passwd().command("passwd");
     let keys   = keys().command("keys");
     let sign   = sign().command("sign");
-    let verify = verify.command("verify");
+    let verify = verify().command("verify");
+    let notary = countersign().command("countersign");
+    let people = contacts().command("contacts");
+    let git    = git().command("git");
     let compat = compat();
 
-    let command = construct!([init, keys, sign, verify, compat]);
+    let command = construct!([init, passwd, keys, sign, verify, notary, people, git, compat]);
 
-    construct!(Args { store, command }).to_options()
+    construct!(Args { store, format, command }).to_options()
 }
 
 fn init() -> OptionParser<Command> {
@@ -86,17 +237,33 @@ fn init() -> OptionParser<Command> {
     construct!(Command::Init(init)).to_options()
 }
 
+fn passwd() -> OptionParser<Command> {
+    let secret = short('s').long("secret").req_flag(true);
+    let fixed  = long("static").req_flag(false);
+    let secret = construct!([secret, fixed]).optional();
+    let passwd = construct!(Passwd { secret });
+    construct!(Command::Passwd(passwd)).to_options()
+}
+
 fn keys() -> OptionParser<Command> {
-    let create = short('c').long("create");
+    let create = create().map(Keys::Create);
     let delete = short('d').long("delete").argument("KEY");
     let export = short('e').long("export").argument("KEY");
     let import = short('i').long("import").argument("FILE");
+    let edit   = long("edit").argument("KEY");
     let public = short('p').long("public").argument("KEY");
+    let allow  = allow().map(Keys::Allow);
+    let signer = signers().map(Keys::Signers);
+    let expiry = long("on-expiry").argument("MODE").map(Keys::Expiry);
+    let rotate = long("rotate").argument("KEY").map(Keys::Rotate);
+    let notice = long("succession").argument("KEY").map(Keys::Statement);
+    let revoke = revoke().map(Keys::Revoke);
+    let krl    = revocations().map(Keys::Revocations);
 
-    let create = create.req_flag(Keys::Create);
     let delete = delete.map(Keys::Delete);
     let export = export.map(Keys::Export);
-    let import = import.map(Keys::Import);
+    let import = construct!(import, describe()).map(|(key, args)| Keys::Import(key, args));
+    let edit   = construct!(edit, describe()).map(|(key, args)| Keys::Edit(key, args));
     let public = public.map(Keys::Public);
 
     let keys = construct!([
@@ -104,20 +271,166 @@ fn keys() -> OptionParser<Command> {
         delete,
         export,
         import,
+        edit,
         public,
+        allow,
+        signer,
+        expiry,
+        rotate,
+        notice,
+        revoke,
+        krl,
     ]).fallback(Keys::List);
 
     construct!(Command::Keys(keys)).to_options()
 }
 
+fn create() -> impl Parser<Create> {
+    let create   = short('c').long("create").req_flag(());
+    let kind     = short('t').long("type").argument("TYPE").fallback(KeyType::Ed25519);
+    let after    = long("valid-after").argument("TIME").optional();
+    let expires  = long("expires").argument("TIME").optional();
+    let describe = describe();
+    construct!(create, kind, after, expires, describe).map(|(_, kind, after, expires, describe)| {
+        Create { kind, after, expires, describe }
+    })
+}
+
+fn describe() -> impl Parser<Describe> {
+    let label      = short('l').long("label").argument("LABEL").optional();
+    let comment    = long("comment").argument("TEXT").optional();
+    let principals = short('P').long("principals").argument::<String>("PRINCIPALS");
+    let principals = principals.map(|list| match list.as_str() {
+        ""   => Vec::new(),
+        list => list.split(',').map(str::to_owned).collect(),
+    }).optional();
+    construct!(Describe { label, comment, principals })
+}
+
+fn revoke() -> impl Parser<Revoke> {
+    let key    = long("revoke").argument("KEY");
+    let reason = long("reason").argument("TEXT").fallback(String::new());
+    construct!(Revoke { key, reason })
+}
+
+fn revocations() -> impl Parser<Revocations> {
+    let revoked  = long("revoked").req_flag(());
+    let keychain = long("keychain").argument("NAME").many();
+    let krl      = long("krl").argument("FILE").optional();
+    construct!(revoked, keychain, krl).map(|(_, keychains, krl)| {
+        Revocations { keychains, krl }
+    })
+}
+
+fn allow() -> impl Parser<Allow> {
+    let key        = short('a').long("allow").argument("KEY");
+    let namespaces = short('n').long("namespaces").argument("NAMESPACES").optional();
+    let after      = long("valid-after").argument("TIME").optional();
+    let before     = long("valid-before").argument("TIME").optional();
+    let principals = positional("PRINCIPALS");
+    construct!(Allow { key, namespaces, after, before, principals })
+}
+
+fn signers() -> impl Parser<Vec<String>> {
+    let signers  = long("allowed-signers").req_flag(());
+    let keychain = long("keychain").argument("NAME").many();
+    construct!(signers, keychain).map(|(_, keychains)| keychains)
+}
+
 fn sign() -> OptionParser<Command> {
-    let key  = short('k').long("key").argument("KEY");
-    let ns   = short('n').long("namespace").argument("NAMESPACE");
-    let data = input("FILE");
-    let sign = construct!(Sign { key, ns, data });
+    let key    = short('k').long("key").argument("KEY");
+    let ns     = short('n').long("namespace").argument("NAMESPACE");
+    let hash   = long("hash").argument("HASH").fallback(HashAlg::Sha256);
+    let append = short('a').long("append").switch();
+    let data   = input("FILE");
+    let sign   = construct!(Sign { key, ns, hash, append, data });
     construct!(Command::Sign(sign)).to_options()
 }
 
+fn verify() -> OptionParser<Command> {
+    let sig       = short('s').long("signature").argument("FILE");
+    let ns        = short('n').long("namespace").argument("NAMESPACE");
+    let revoked   = short('r').long("revoked").argument("FILE").optional();
+    let threshold = short('t').long("threshold").argument("N").optional();
+    let signers   = long("signers").argument("GROUP").optional();
+    let time      = long("verify-time").argument("TIME").optional();
+    let data      = input("FILE");
+    let verify    = construct!(Verify { sig, ns, revoked, threshold, signers, time, data });
+    construct!(Command::Verify(verify)).to_options()
+}
+
+fn countersign() -> OptionParser<Command> {
+    let key  = short('k').long("key").argument("KEY");
+    let hash = long("hash").argument("HASH").fallback(HashAlg::Sha256);
+    let sig  = positional("SIGNATURE");
+    let sign = construct!(Countersign { key, hash, sig });
+    construct!(Command::Countersign(sign)).to_options()
+}
+
+fn contacts() -> OptionParser<Command> {
+    let add     = add().map(Contacts::Add);
+    let import  = import().map(Contacts::Import);
+    let refresh = long("refresh").req_flag(Contacts::Refresh);
+    let rotated = long("succession").argument::<PathBuf>("FILE").map(|path| {
+        Contacts::Succession(path.into())
+    });
+    let delete  = short('d').long("delete").argument("ID").map(Contacts::Delete);
+
+    let contacts = construct!([
+        add,
+        import,
+        refresh,
+        rotated,
+        delete,
+    ]).fallback(Contacts::List);
+
+    construct!(Command::Contacts(contacts)).to_options()
+}
+
+fn add() -> impl Parser<Add> {
+    let key        = short('a').long("add").argument::<PathBuf>("FILE");
+    let key        = key.map(|path| match path.to_str() {
+        Some("-") => Input::Stdin,
+        _         => Input::File(path),
+    });
+    let principals = principals();
+    let note       = long("note").argument("TEXT").fallback(String::new());
+    let trust      = long("trust").argument("LEVEL").fallback(Trust::Full);
+    construct!(Add { key, principals, note, trust })
+}
+
+fn import() -> impl Parser<Import> {
+    let source     = short('i').long("import").argument("SOURCE");
+    let principals = principals();
+    let note       = long("note").argument("TEXT").fallback(String::new());
+    let trust      = long("trust").argument("LEVEL").fallback(Trust::Full);
+    construct!(Import { source, principals, note, trust })
+}
+
+fn principals() -> impl Parser<Vec<String>> {
+    let principals = short('P').long("principals").argument::<String>("PRINCIPALS");
+    principals.map(|list| {
+        list.split(',').map(str::to_owned).collect()
+    }).fallback(Vec::new())
+}
+
+fn git() -> OptionParser<Command> {
+    let key    = short('k').long("key").argument("KEY").optional();
+    let global = short('g').long("global").switch();
+    let undo   = short('u').long("undo").switch();
+    let setup  = construct!(Setup { key, global, undo });
+    let setup  = construct!(Git::Setup(setup)).to_options().command("setup");
+
+    let range   = positional("RANGE");
+    let first   = long("first-parent").switch();
+    let signers = short('s').long("signers").argument("FILE").optional();
+    let verify  = construct!(Commits { first, signers, range });
+    let verify  = construct!(Git::Verify(verify)).to_options().command("verify");
+
+    let git = construct!([setup, verify]);
+    construct!(Command::Git(git)).to_options()
+}
+
 fn input(name: &'static str) -> impl Parser<Input> {
     positional::<PathBuf>(name).optional().map(|path| {
         match path {
@@ -134,41 +447,55 @@ fn compat() -> impl Parser<Command> {
 
     let key    = short('f').argument::<String>("").hide();
     let ns     = short('n').argument::<String>("").hide();
+    let agent  = short('U').switch().hide();
     let file   = input("FILE");
-    let sign   = construct!(key, ns, file);
-    let sign   = sign.parse(|(key, ns, data)| {
-        Ok::<_, String>(Command::Sign(Sign { key, ns, data }))
+    let sign   = construct!(key, ns, agent, file);
+    let sign   = sign.parse(|(key, ns, _, data)| {
+        let hash   = HashAlg::Sha512;
+        let append = false;
+        Ok::<_, String>(Compat::Sign(Sign { key, ns, hash, append, data }))
     }).to_options().command("sign").hide();
 
-    let file   = short('f').argument::<Input>("").hide();
+    let file   = short('f').argument::<PathBuf>("").hide();
     let id     = short('I').argument::<String>("").hide();
     let ns     = short('n').argument::<String>("").hide();
     let sig    = short('s').argument::<PathBuf>("").hide();
     let rev    = short('r').argument::<PathBuf>("").optional().hide();
     let verify = construct!(file, id, ns, sig, rev);
-    let verify = verify.parse(|(_, _, ns, sig, _)| {
-        let data = Input::Stdin;
-        Ok::<_, String>(Command::Verify(Verify { sig, ns, data }))
+    let verify = verify.parse(|(signers, identity, ns, sig, revoked)| {
+        let allowed = Allowed { signers, identity, ns, sig, revoked };
+        Ok::<_, String>(Compat::Verify(allowed))
     }).to_options().command("verify").hide();
 
     let file   = short('f').argument::<PathBuf>("").hide();
     let sig    = short('s').argument::<PathBuf>("").hide();
     let find   = construct!(file, sig);
-    let find   = find.parse(|(_, _)| {
-        Ok::<_, String>(Command::Compat)
+    let find   = find.parse(|(signers, sig)| {
+        let find = Find { signers, sig };
+        Ok::<_, String>(Compat::Find(find))
     }).to_options().command("find-principals").hide();
 
+    let file   = short('f').argument::<PathBuf>("").hide();
+    let id     = short('I').argument::<String>("").hide();
+    let names  = construct!(file, id);
+    let names  = names.parse(|(signers, identity)| {
+        let names = Match { signers, identity };
+        Ok::<_, String>(Compat::Match(names))
+    }).to_options().command("match-principals").hide();
+
     let ns     = short('n').argument::<String>("").hide();
     let sig    = short('s').argument::<PathBuf>("").hide();
     let check  = construct!(ns, sig);
     let check  = check.parse(|(ns, sig)| {
-        let data = Input::Stdin;
-        Ok::<_, String>(Command::Verify(Verify { sig, ns, data }))
+        let check = Check { ns, sig };
+        Ok::<_, String>(Compat::Check(check))
     }).to_options().command("check-novalidate").hide();
 
-    let command = construct!([sign, verify, check, find]);
+    let command = construct!([sign, verify, check, find, names]);
 
-    construct!(flag, opts, command).parse(|(_, _, cmd)| Ok::<_, String>(cmd))
+    construct!(flag, opts, command).parse(|(_, opts, cmd)| {
+        Ok::<_, String>(Command::Compat(cmd, opts))
+    })
 }
 
 fn store_dir() -> Result<PathBuf> {
@@ -0,0 +1,288 @@
+use std::path::Path;
+use std::process::ExitCode;
+use anyhow::{anyhow, Result};
+use ssh_key::{Algorithm, HashAlg, PublicKey};
+use ssh_key::public::KeyData;
+use time::OffsetDateTime;
+use crate::{Input, Signet, System};
+use crate::args::{Allowed, Check, Compat, Find, Match, Sign};
+use crate::persist::read;
+use crate::signers::{self, AllowedSigners, Query, Signer};
+use crate::sshsig::SshSig;
+use crate::timestamp::{self, now};
+use super::Format;
+use super::verify::{check, unrevoked, Error};
+
+#[derive(Debug)]
+struct Options {
+    hash:   Option<HashAlg>,
+    time:   OffsetDateTime,
+    pubkey: bool,
+    touch:  bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Opt {
+    Hash,
+    Time,
+    Pubkey,
+    Touch,
+}
+
+pub fn compat<S: System>(signet: &Signet<S>, cmd: Compat, opts: &[String]) -> ExitCode {
+    match cmd {
+        Compat::Sign(cmd)   => sign(signet, cmd, opts),
+        Compat::Find(cmd)   => find::<S>(cmd, opts),
+        Compat::Match(cmd)  => matching::<S>(cmd, opts),
+        Compat::Verify(cmd) => verify::<S>(cmd, opts),
+        Compat::Check(cmd)  => novalidate::<S>(cmd, opts),
+    }
+}
+
+fn sign<S: System>(signet: &Signet<S>, args: Sign, opts: &[String]) -> ExitCode {
+    let opts = match options(opts, &[Opt::Hash]) {
+        Ok(opts)   => opts,
+        Err(error) => return invalid(error),
+    };
+
+    let sign = || {
+        let hash = opts.hash.unwrap_or(args.hash);
+        super::sign(signet, Sign { hash, ..args }, Format::Text)
+    };
+
+    match sign() {
+        Ok(())     => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            failure()
+        },
+    }
+}
+
+fn find<S: System>(args: Find, opts: &[String]) -> ExitCode {
+    let opts = match options(opts, &[Opt::Time, Opt::Touch]) {
+        Ok(opts)   => opts,
+        Err(error) => return invalid(error),
+    };
+
+    match principals::<S>(&args, &opts) {
+        Ok(Some(signer)) => {
+            signer.principals().for_each(|principal| {
+                println!("{principal}");
+            });
+            return ExitCode::SUCCESS;
+        },
+        Ok(None)   => (),
+        Err(error) => eprintln!("{error}"),
+    };
+    eprintln!("No principal matched.");
+    failure()
+}
+
+fn principals<S: System>(args: &Find, opts: &Options) -> Result<Option<Signer>> {
+    let sig     = signature::<S>(&args.sig)?;
+    let signers = signers::<S>(&args.signers)?;
+
+    let query = Query {
+        key:       sig.public_key(),
+        principal: None,
+        namespace: None,
+        time:      opts.time,
+    };
+
+    Ok(first(signers.find(&query)))
+}
+
+fn matching<S: System>(args: Match, opts: &[String]) -> ExitCode {
+    if let Err(error) = options(opts, &[]) {
+        return invalid(error);
+    }
+
+    match matches::<S>(&args) {
+        Ok(signers) if !signers.is_empty() => {
+            signers.iter().for_each(|signer| {
+                println!("{}", signer.principals);
+            });
+            return ExitCode::SUCCESS;
+        },
+        Ok(_)      => (),
+        Err(error) => eprintln!("{error}"),
+    };
+    eprintln!("No principal matched.");
+    failure()
+}
+
+fn matches<S: System>(args: &Match) -> Result<Vec<Signer>> {
+    let signers = signers::<S>(&args.signers)?;
+    Ok(signers.principals(&args.identity)?)
+}
+
+fn verify<S: System>(args: Allowed, opts: &[String]) -> ExitCode {
+    let opts = match options(opts, &[Opt::Time, Opt::Pubkey, Opt::Touch]) {
+        Ok(opts)   => opts,
+        Err(error) => return invalid(error),
+    };
+
+    match allowed::<S>(&args, &opts) {
+        Ok(Some(key)) => {
+            let Allowed { identity, ns, .. } = &args;
+            let kind = kind(&key);
+            let fp   = key.fingerprint(HashAlg::Sha256);
+            println!("Good \"{ns}\" signature for {identity} with {kind} key {fp}");
+            pubkey(&key, &opts);
+            return ExitCode::SUCCESS;
+        },
+        Ok(None)   => (),
+        Err(error) => eprintln!("{error}"),
+    };
+    println!("Could not verify signature.");
+    failure()
+}
+
+fn allowed<S: System>(args: &Allowed, opts: &Options) -> Result<Option<KeyData>> {
+    let sig = signature::<S>(&args.sig)?;
+    let msg = read::<S>(&Input::Stdin)?;
+
+    valid(&sig, &args.ns, &msg)?;
+
+    if opts.touch && sig.sk().is_some_and(|sk| !sk.presence()) {
+        return Err(anyhow!("Signature lacks user presence"));
+    }
+
+    if let Some(path) = &args.revoked {
+        unrevoked::<S>(path, sig.public_key())?;
+    }
+
+    let signers = signers::<S>(&args.signers)?;
+
+    let query = Query {
+        key:       sig.public_key(),
+        principal: Some(&args.identity),
+        namespace: Some(&args.ns),
+        time:      opts.time,
+    };
+
+    let signer = first(signers.find(&query));
+    Ok(signer.map(|_| sig.public_key().clone()))
+}
+
+fn novalidate<S: System>(args: Check, opts: &[String]) -> ExitCode {
+    let opts = match options(opts, &[Opt::Time, Opt::Pubkey]) {
+        Ok(opts)   => opts,
+        Err(error) => return invalid(error),
+    };
+
+    match validate::<S>(&args) {
+        Ok(key) => {
+            let Check { ns, .. } = &args;
+            let kind = kind(&key);
+            let fp   = key.fingerprint(HashAlg::Sha256);
+            println!("Good \"{ns}\" signature with {kind} key {fp}");
+            pubkey(&key, &opts);
+            return ExitCode::SUCCESS;
+        },
+        Err(error) => eprintln!("{error}"),
+    };
+    println!("Could not verify signature.");
+    failure()
+}
+
+fn validate<S: System>(args: &Check) -> Result<KeyData> {
+    let sig = signature::<S>(&args.sig)?;
+    let msg = read::<S>(&Input::Stdin)?;
+    let key = valid(&sig, &args.ns, &msg)?;
+    Ok(key.key_data().clone())
+}
+
+fn valid(sig: &SshSig, ns: &str, msg: &[u8]) -> Result<PublicKey> {
+    check(sig, ns, msg).map_err(|error| match error {
+        Error::Namespace => anyhow!("Couldn't verify signature: {error}"),
+        error            => anyhow!("Signature verification failed: {error}"),
+    })
+}
+
+fn signature<S: System>(path: &Path) -> Result<SshSig> {
+    let sig = read::<S>(&path.to_owned().into())?;
+    Ok(SshSig::from_pem(sig)?)
+}
+
+fn signers<S: System>(path: &Path) -> Result<AllowedSigners> {
+    let data = read::<S>(&path.to_owned().into())?;
+    Ok(AllowedSigners::new(path, &data))
+}
+
+fn first(found: impl Iterator<Item = Result<Signer, signers::Error>>) -> Option<Signer> {
+    for result in found {
+        match result {
+            Ok(signer) => return Some(signer),
+            Err(error) => eprintln!("{error}"),
+        }
+    }
+    None
+}
+
+fn options(opts: &[String], accept: &[Opt]) -> Result<Options> {
+    let mut options = Options {
+        hash:   None,
+        time:   now(),
+        pubkey: false,
+        touch:  true,
+    };
+
+    for opt in opts {
+        if let Some(hash) = strip(opt, "hashalg=").filter(|_| accept.contains(&Opt::Hash)) {
+            let hash = hash.parse().map_err(|_| {
+                anyhow!("unsupported hash algorithm \"{hash}\"")
+            })?;
+            options.hash = Some(hash);
+        } else if let Some(time) = strip(opt, "verify-time=").filter(|_| accept.contains(&Opt::Time)) {
+            let time = timestamp::parse(time).ok_or_else(|| {
+                anyhow!("Invalid \"verify-time\" option")
+            })?;
+            options.time = time;
+        } else if opt.eq_ignore_ascii_case("print-pubkey") && accept.contains(&Opt::Pubkey) {
+            options.pubkey = true;
+        } else if opt.eq_ignore_ascii_case("no-touch-required") && accept.contains(&Opt::Touch) {
+            options.touch = false;
+        } else {
+            return Err(anyhow!("Invalid option \"{opt}\""));
+        }
+    }
+
+    Ok(options)
+}
+
+fn strip<'a>(opt: &'a str, name: &str) -> Option<&'a str> {
+    let (head, tail) = (opt.get(..name.len())?, opt.get(name.len()..)?);
+    head.eq_ignore_ascii_case(name).then_some(tail)
+}
+
+fn pubkey(key: &KeyData, opts: &Options) {
+    if !opts.pubkey {
+        return;
+    }
+    if let Ok(key) = PublicKey::from(key.clone()).to_openssh() {
+        println!("{key}");
+    }
+}
+
+fn kind(key: &KeyData) -> &'static str {
+    match key.algorithm() {
+        Algorithm::Dsa                 => "DSA",
+        Algorithm::Ecdsa { .. }        => "ECDSA",
+        Algorithm::Ed25519             => "ED25519",
+        Algorithm::Rsa { .. }          => "RSA",
+        Algorithm::SkEcdsaSha2NistP256 => "ECDSA-SK",
+        Algorithm::SkEd25519           => "ED25519-SK",
+        _                              => "unknown",
+    }
+}
+
+fn failure() -> ExitCode {
+    ExitCode::from(255)
+}
+
+fn invalid(error: anyhow::Error) -> ExitCode {
+    eprintln!("{error}");
+    ExitCode::from(1)
+}
@@ -0,0 +1,225 @@
+use std::env;
+use std::io::Read;
+use anyhow::{anyhow, Result};
+use ssh_key::{HashAlg, PublicKey};
+use crate::{Contact, Input, Signet, Source, Succession, System};
+use crate::args::{self, Add, Import};
+use crate::persist::read;
+
+const LIMIT: u64 = 1 << 20;
+
+pub fn contacts<S: System>(signet: &Signet<S>, cmd: args::Contacts) -> Result<()> {
+    match cmd {
+        args::Contacts::Add(args)        => add(signet, args),
+        args::Contacts::Import(args)     => import(signet, args),
+        args::Contacts::Refresh          => refresh(signet),
+        args::Contacts::Succession(file) => succession(signet, &file),
+        args::Contacts::Delete(id)       => delete(signet, &id),
+        args::Contacts::List             => list(signet),
+    }
+}
+
+pub fn add<S: System>(signet: &Signet<S>, args: Add) -> Result<()> {
+    let Add { key, principals, note, trust } = args;
+
+    let mut contacts = signet.contacts()?;
+
+    let key = read::<S>(&key)?;
+    let key = PublicKey::from_openssh(String::from_utf8_lossy(&key).trim())?;
+
+    let id = contacts.add(Contact { key, principals, note, trust, source: None });
+    signet.save(&contacts)?;
+
+    Ok(println!("added contact {id}"))
+}
+
+pub fn import<S: System>(signet: &Signet<S>, args: Import) -> Result<()> {
+    let Import { source: location, principals, note, trust } = args;
+
+    let mut contacts = signet.contacts()?;
+
+    let source   = Source { principals, note, trust };
+    let location = locate(&location)?;
+    let keys     = fetch::<S>(&location)?;
+
+    let location = match location.as_str() {
+        "-"      => None,
+        location => Some(location),
+    };
+
+    let mut added   = Vec::new();
+    let mut present = Vec::new();
+    for key in keys {
+        let contact = source.contact(key, location);
+        if contact.principals.is_empty() {
+            return Err(anyhow!("no principal for key {}, use --principals", contact.id()));
+        }
+        match contacts.contains(&contact.id()) {
+            true  => present.push(contact.id()),
+            false => added.push(contacts.add(contact)),
+        }
+    }
+
+    if let Some(location) = location {
+        contacts.import(location, source);
+    }
+
+    signet.save(&contacts)?;
+
+    for id in added {
+        println!("added contact {id}");
+    }
+
+    for id in present {
+        println!("contact {id} already present");
+    }
+
+    Ok(())
+}
+
+pub fn refresh<S: System>(signet: &Signet<S>) -> Result<()> {
+    let mut contacts = signet.contacts()?;
+
+    let sources = contacts.sources().map(|(location, source)| {
+        (location.clone(), source.clone())
+    }).collect::<Vec<_>>();
+
+    let mut changes = Vec::new();
+    for (location, source) in sources {
+        let keys = fetch::<S>(&location)?;
+        let keys = keys.into_iter().map(|key| {
+            source.contact(key, Some(&location))
+        }).collect::<Vec<_>>();
+
+        for id in contacts.imported(&location) {
+            if !keys.iter().any(|contact| contact.id() == id) {
+                contacts.delete(&id);
+                changes.push(format!("removed contact {id}"));
+            }
+        }
+
+        for contact in keys {
+            if !contacts.contains(&contact.id()) {
+                let id = contacts.add(contact);
+                changes.push(format!("added contact {id}"));
+            }
+        }
+    }
+
+    signet.save(&contacts)?;
+
+    match changes.is_empty() {
+        true  => Ok(println!("contacts are up to date")),
+        false => Ok(println!("{}", changes.join("\n"))),
+    }
+}
+
+pub fn succession<S: System>(signet: &Signet<S>, file: &Input) -> Result<()> {
+    let mut contacts = signet.contacts()?;
+
+    let data       = read::<S>(file)?;
+    let succession = serde_yaml::from_slice::<Succession>(&data)?;
+    succession.verify()?;
+
+    let old = hex::encode(succession.old.fingerprint(HashAlg::Sha256));
+    let new = hex::encode(succession.new.fingerprint(HashAlg::Sha256));
+
+    let known = contacts.contains(&old) || contacts.successions().any(|known| {
+        known.succeeds(&succession.old)
+    });
+
+    if !known {
+        return Err(anyhow!("key {old} is not a contact"));
+    }
+
+    contacts.rotate(succession);
+    signet.save(&contacts)?;
+
+    Ok(println!("contact {old} succeeded by {new}"))
+}
+
+pub fn delete<S: System>(signet: &Signet<S>, id: &str) -> Result<()> {
+    let mut contacts = signet.contacts()?;
+
+    let contact = contacts.find(id)?;
+    let id      = contact.id();
+
+    contacts.delete(&id);
+    signet.save(&contacts)?;
+
+    Ok(println!("deleted contact {id}"))
+}
+
+pub fn list<S: System>(signet: &Signet<S>) -> Result<()> {
+    let contacts = signet.contacts()?;
+
+    let list = contacts.contacts().map(|contact| {
+        let id         = contact.id();
+        let trust      = contact.trust.to_string();
+        let principals = contact.principals.join(",");
+        let line       = format!("{id:>66} {trust:<8} {principals} {}", contact.note);
+        line.trim_end().to_owned()
+    }).collect::<Vec<_>>().join("\n");
+
+    Ok(println!("contacts:\n{list}"))
+}
+
+fn locate(location: &str) -> Result<String> {
+    if location == "-" || remote(location) {
+        return Ok(location.to_owned());
+    }
+    let path = env::current_dir()?.join(location);
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn fetch<S: System>(location: &str) -> Result<Vec<PublicKey>> {
+    let data = match location {
+        "-"                => read::<S>(&Input::Stdin)?,
+        url if remote(url) => download(url)?,
+        path               => read::<S>(&Input::File(path.into()))?,
+    };
+    parse(&String::from_utf8_lossy(&data)).map_err(|e| anyhow!("{location}: {e}"))
+}
+
+fn remote(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+fn insecure(url: &str) -> bool {
+    let Some(rest) = url.strip_prefix("http://") else { return false };
+    let authority  = rest.split('/').next().unwrap_or_default();
+
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _                                                              => authority,
+    };
+
+    !matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+fn download(url: &str) -> Result<Vec<u8>> {
+    if insecure(url) {
+        return Err(anyhow!("{url}: refusing to fetch keys over http, use https"));
+    }
+
+    let response = ureq::get(url).call()?;
+    let mut data = Vec::new();
+    response.into_reader().take(LIMIT + 1).read_to_end(&mut data)?;
+
+    match data.len() as u64 > LIMIT {
+        true  => Err(anyhow!("{url}: response exceeds {LIMIT} bytes")),
+        false => Ok(data),
+    }
+}
+
+fn parse(data: &str) -> Result<Vec<PublicKey>> {
+    data.lines().map(str::trim).filter(|line| {
+        !line.is_empty() && !line.starts_with('#')
+    }).map(|line| {
+        let starts = line.match_indices(char::is_whitespace).map(|(n, _)| n + 1);
+        let starts = [0].into_iter().chain(starts);
+        starts.map(|n| line[n..].trim_start()).find_map(|key| {
+            PublicKey::from_openssh(key).ok()
+        }).ok_or_else(|| anyhow!("invalid key '{line}'"))
+    }).collect()
+}
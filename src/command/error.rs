@@ -0,0 +1,211 @@
+use std::io;
+use std::process::ExitCode;
+use anyhow::Error;
+use serde::Serialize;
+use crate::{contacts, git, keychain, persist, platform, succession};
+use crate::keychain::password;
+use super::verify;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Other,
+    KeyNotFound,
+    KeyAmbiguous,
+    BadPassword,
+    BadSignature,
+    UntrustedSigner,
+    StoreCorrupt,
+    KeyringUnavailable,
+    Io,
+    KeyExpired,
+    KeyRetired,
+    WeakKey,
+}
+
+impl Kind {
+    pub fn of(error: &Error) -> Self {
+        error.chain().map(classify).find(|kind| {
+            *kind != Self::Other
+        }).unwrap_or(Self::Other)
+    }
+
+    pub fn code(self) -> u8 {
+        match self {
+            Self::Other              => 1,
+            Self::KeyNotFound        => 2,
+            Self::KeyAmbiguous       => 3,
+            Self::BadPassword        => 4,
+            Self::BadSignature       => 5,
+            Self::UntrustedSigner    => 6,
+            Self::StoreCorrupt       => 7,
+            Self::KeyringUnavailable => 8,
+            Self::Io                 => 9,
+            Self::KeyExpired         => 10,
+            Self::KeyRetired         => 11,
+            Self::WeakKey            => 12,
+        }
+    }
+}
+
+impl From<Kind> for ExitCode {
+    fn from(kind: Kind) -> Self {
+        ExitCode::from(kind.code())
+    }
+}
+
+fn classify(cause: &(dyn std::error::Error + 'static)) -> Kind {
+    if let Some(error) = cause.downcast_ref::<keychain::Error>() {
+        Kind::from(error)
+    } else if let Some(error) = cause.downcast_ref::<contacts::Error>() {
+        Kind::from(error)
+    } else if let Some(error) = cause.downcast_ref::<password::Error>() {
+        Kind::from(error)
+    } else if let Some(error) = cause.downcast_ref::<verify::Error>() {
+        Kind::from(error)
+    } else if let Some(error) = cause.downcast_ref::<persist::Error>() {
+        Kind::from(error)
+    } else if let Some(error) = cause.downcast_ref::<git::Error>() {
+        Kind::from(error)
+    } else if let Some(error) = cause.downcast_ref::<succession::Error>() {
+        Kind::from(error)
+    } else if let Some(error) = cause.downcast_ref::<ssh_key::Error>() {
+        Kind::from(error)
+    } else if cause.is::<platform::Error>() {
+        Kind::KeyringUnavailable
+    } else if cause.is::<io::Error>() {
+        Kind::Io
+    } else {
+        Kind::Other
+    }
+}
+
+impl From<&keychain::Error> for Kind {
+    fn from(error: &keychain::Error) -> Self {
+        match error {
+            keychain::Error::KeyNotFound        => Self::KeyNotFound,
+            keychain::Error::KeyAmbiguous(_)    => Self::KeyAmbiguous,
+            keychain::Error::KeyExpired(_)      => Self::KeyExpired,
+            keychain::Error::KeyNotYetValid(_)  => Self::KeyExpired,
+            keychain::Error::KeyRetired(_)      => Self::KeyRetired,
+            keychain::Error::KeyRevoked(_)      => Self::UntrustedSigner,
+            keychain::Error::WeakKey(_)         => Self::WeakKey,
+            keychain::Error::InvalidExpiry(_)   => Self::Other,
+            keychain::Error::InvalidKeyType(_)  => Self::Other,
+            keychain::Error::InvalidSelector(_) => Self::Other,
+            keychain::Error::InvalidLabel(_)    => Self::Other,
+        }
+    }
+}
+
+impl From<&contacts::Error> for Kind {
+    fn from(error: &contacts::Error) -> Self {
+        match error {
+            contacts::Error::ContactNotFound  => Self::KeyNotFound,
+            contacts::Error::ContactAmbiguous => Self::KeyAmbiguous,
+            contacts::Error::InvalidTrust(_)  => Self::Other,
+        }
+    }
+}
+
+impl From<&password::Error> for Kind {
+    fn from(error: &password::Error) -> Self {
+        match error {
+            password::Error::Incorrect  => Self::BadPassword,
+            password::Error::Mismatch   => Self::BadPassword,
+            password::Error::Crypto(_)  => Self::Other,
+            password::Error::Keyring(_) => Self::KeyringUnavailable,
+            password::Error::System(_)  => Self::Io,
+        }
+    }
+}
+
+impl From<&verify::Error> for Kind {
+    fn from(error: &verify::Error) -> Self {
+        match error {
+            verify::Error::Namespace     => Self::BadSignature,
+            verify::Error::Signature     => Self::BadSignature,
+            verify::Error::Revoked       => Self::UntrustedSigner,
+            verify::Error::Untrusted     => Self::UntrustedSigner,
+            verify::Error::Policy        => Self::UntrustedSigner,
+            verify::Error::Threshold(..) => Self::UntrustedSigner,
+        }
+    }
+}
+
+impl From<&persist::Error> for Kind {
+    fn from(error: &persist::Error) -> Self {
+        match error.invalid() {
+            true  => Self::StoreCorrupt,
+            false => Self::Io,
+        }
+    }
+}
+
+impl From<&git::Error> for Kind {
+    fn from(error: &git::Error) -> Self {
+        match error {
+            git::Error::System(..) => Self::Io,
+            _                      => Self::Other,
+        }
+    }
+}
+
+impl From<&succession::Error> for Kind {
+    fn from(_: &succession::Error) -> Self {
+        Self::BadSignature
+    }
+}
+
+impl From<&ssh_key::Error> for Kind {
+    fn from(error: &ssh_key::Error) -> Self {
+        match error {
+            ssh_key::Error::Crypto => Self::BadPassword,
+            ssh_key::Error::Io(_)  => Self::Io,
+            _                      => Self::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::path::Path;
+    use anyhow::anyhow;
+    use time::OffsetDateTime;
+    use crate::{keychain, platform};
+    use crate::keychain::password;
+    use crate::persist::Context;
+    use super::super::verify;
+    use super::Kind;
+
+    #[test]
+    fn codes() {
+        let now     = OffsetDateTime::now_utc();
+        let corrupt = serde_yaml::from_str::<u8>("[").context(Path::new("signet.yml")).unwrap_err();
+        let missing = Err::<(), _>(io::Error::from(io::ErrorKind::PermissionDenied)).context(Path::new("signet.yml")).unwrap_err();
+
+        let errors = [
+            (anyhow!("usage"),                                            Kind::Other,              1),
+            (keychain::Error::KeyNotFound.into(),                         Kind::KeyNotFound,        2),
+            (keychain::Error::KeyAmbiguous(Vec::new()).into(),            Kind::KeyAmbiguous,       3),
+            (password::Error::Incorrect.into(),                           Kind::BadPassword,        4),
+            (verify::Error::Signature.into(),                             Kind::BadSignature,       5),
+            (verify::Error::Threshold(1, 2).into(),                       Kind::UntrustedSigner,    6),
+            (corrupt.into(),                                              Kind::StoreCorrupt,       7),
+            (platform::Error::Keyring(keyring::Error::NoEntry).into(),    Kind::KeyringUnavailable, 8),
+            (missing.into(),                                              Kind::Io,                 9),
+            (keychain::Error::KeyNotYetValid(now).into(),                 Kind::KeyExpired,         10),
+            (keychain::Error::KeyRetired(String::new()).into(),           Kind::KeyRetired,         11),
+            (keychain::Error::WeakKey("DSA".to_owned()).into(),           Kind::WeakKey,            12),
+        ];
+
+        for (error, kind, code) in errors {
+            assert_eq!(Kind::of(&error), kind, "{error:#}");
+            assert_eq!(kind.code(), code);
+        }
+
+        let error = anyhow::Error::from(keychain::Error::KeyExpired(now)).context("signing");
+        assert_eq!(Kind::of(&error), Kind::KeyExpired);
+    }
+}
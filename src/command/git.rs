@@ -0,0 +1,446 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::env;
+use std::io::ErrorKind;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use ssh_key::HashAlg;
+use time::OffsetDateTime;
+use crate::{Contacts, Key, Keychain, Revocations, Signet, System, Trust};
+use crate::args::{Commits, Git, Setup};
+use crate::git::{global, Commit, Config, Oid, Repository, Signed};
+use crate::persist::{self, Context};
+use crate::signers::{AllowedSigners, Query};
+use crate::sshsig::SshSig;
+use crate::timestamp::now;
+use super::keys::selector;
+use super::verify::{check, Error};
+
+const RECORD: &str = "signet.setup";
+const SLOP:   usize = 5;
+
+#[derive(Debug)]
+enum Status {
+    Good(String),
+    Unknown(String),
+    Bad(String),
+    Unsigned,
+}
+
+struct Policy {
+    signers:  Option<AllowedSigners>,
+    keychain: Option<Keychain>,
+    contacts: Option<Contacts>,
+    revoked:  Option<Revocations>,
+}
+
+pub fn git<S: System>(signet: &Signet<S>, cmd: Git) -> Result<()> {
+    match cmd {
+        Git::Setup(cmd)  => setup(signet, cmd),
+        Git::Verify(cmd) => verify(signet, cmd),
+    }
+}
+
+pub fn setup<S: System>(signet: &Signet<S>, args: Setup) -> Result<()> {
+    let Setup { key, global, undo } = args;
+
+    let path = match global {
+        true  => self::global::<S>(),
+        false => repository::<S>()?.map(|repo| repo.config()),
+    }.ok_or_else(|| anyhow!("cannot locate git config"))?;
+
+    let mut config = load::<S>(&path)?;
+    let program    = env::current_exe()?.to_string_lossy().into_owned();
+    let signers    = signet.root().join("allowed_signers");
+
+    if undo {
+        return self::undo(signet, &path, config, &program, &signers);
+    }
+
+    let keychain = signet.current()?;
+    let key      = match key {
+        Some(key) => keychain.find(&selector::<S>(&key)?)?,
+        None      => only(&keychain)?,
+    };
+    let public   = key.public_key().to_string();
+
+    let email = match config.get("user.email") {
+        Some(email) => Some(email),
+        None        => match self::global::<S>() {
+            Some(path) => load::<S>(&path)?.get("user.email"),
+            None       => None,
+        },
+    }.ok_or_else(|| anyhow!("git user.email is not set"))?;
+
+    let entry  = format!("{email} namespaces=\"git\" {public}");
+    let signer = format!("{RECORD}.signer");
+    if let Some(old) = config.get(&signer).filter(|old| *old != entry) {
+        disallow::<S>(&signers, &old)?;
+        config.unset(&signer);
+    }
+    if allow::<S>(&signers, &entry)? {
+        config.set(&signer, &entry);
+    }
+
+    let settings = settings(&format!("key::{public}"), &program, &signers);
+
+    if config.get("gpg.ssh.program").as_deref() != Some(&program) {
+        for (name, value) in &settings {
+            if let Some(old) = config.get(name).filter(|old| old != value) {
+                config.set(&previous(name), &old);
+            }
+        }
+    }
+
+    for (name, value) in &settings {
+        config.set(name, value);
+    }
+    save::<S>(&path, &config)?;
+
+    Ok(println!("configured {} to sign with key {}", path.display(), key.id()))
+}
+
+fn only(keychain: &Keychain) -> Result<&Key> {
+    let keys = keychain.keys().filter(|key| {
+        keychain.usable(&key.id()).is_ok()
+    }).collect::<Vec<_>>();
+
+    match keys[..] {
+        [key] => Ok(key),
+        []    => Err(anyhow!("keychain has no usable keys")),
+        [..]  => Err(anyhow!("keychain has {} keys, select one with --key", keys.len())),
+    }
+}
+
+fn undo<S: System>(signet: &Signet<S>, path: &Path, mut config: Config, program: &str, signers: &Path) -> Result<()> {
+    let key = config.get("user.signingkey").filter(|key| {
+        signet.current().is_ok_and(|keychain| keychain.keys().any(|own| {
+            *key == format!("key::{}", own.public_key().to_string())
+        }))
+    }).unwrap_or_default();
+
+    for (name, value) in settings(&key, program, signers) {
+        let previous = previous(name);
+        if config.get(name) == Some(value) {
+            match config.get(&previous) {
+                Some(old) => config.set(name, &old),
+                None      => drop(config.unset(name)),
+            }
+        }
+        config.unset(&previous);
+    }
+
+    let signer = format!("{RECORD}.signer");
+    if let Some(entry) = config.get(&signer) {
+        disallow::<S>(signers, &entry)?;
+        config.unset(&signer);
+    }
+
+    save::<S>(path, &config)?;
+    Ok(println!("removed signing config from {}", path.display()))
+}
+
+pub fn verify<S: System>(signet: &Signet<S>, args: Commits) -> Result<()> {
+    let Commits { range, first, signers } = args;
+
+    let repo   = repository::<S>()?.ok_or_else(|| anyhow!("not a git repository"))?;
+    let config = load::<S>(&repo.config())?;
+    let global = match self::global::<S>() {
+        Some(path) => load::<S>(&path)?,
+        None       => Config::default(),
+    };
+    let path = |name: &str| config.path(name).or_else(|| global.path(name));
+
+    let signers = match signers.or_else(|| path("gpg.ssh.allowedSignersFile")) {
+        Some(path) => Some(AllowedSigners::new(&path, &read::<S>(&path)?.unwrap_or_default())),
+        None       => None,
+    };
+
+    let keychain = match (&signers, signet.current()) {
+        (_, Ok(keychain))                => Some(keychain),
+        (Some(_), Err(e)) if missing(&e) => None,
+        (_, Err(e))                      => return Err(e),
+    };
+
+    let contacts = match signers {
+        Some(_) => None,
+        None    => Some(signet.contacts()?),
+    };
+
+    let revoked = match path("gpg.ssh.revocationFile") {
+        Some(path) => Some(Revocations::parse(&read::<S>(&path)?.unwrap_or_default()).map_err(|e| {
+            anyhow!("{}: {e}", path.display())
+        })?),
+        None       => None,
+    };
+
+    if range.contains("...") {
+        return Err(anyhow!("symmetric difference ranges are not supported"));
+    }
+
+    let (hide, show) = match range.split_once("..") {
+        Some((hide, show)) => (Some(hide), show),
+        None               => (None, &range[..]),
+    };
+
+    let show = repo.peel(&repo.resolve(show)?)?;
+    let hide = match hide {
+        Some(rev) => Some(repo.peel(&repo.resolve(rev)?)?),
+        None      => None,
+    };
+
+    let mut tags = HashMap::<Oid, Vec<_>>::new();
+    for (name, oid) in repo.tags() {
+        if let Some(tag) = repo.tag(&oid)? {
+            let name = name.trim_start_matches("refs/tags/").to_owned();
+            tags.entry(repo.peel(&oid)?).or_default().push((name, tag));
+        }
+    }
+
+    let policy = Policy { signers, keychain, contacts, revoked };
+
+    let mut total  = 0;
+    let mut failed = 0;
+    let mut report = |kind: &str, name: &str, status: Status| {
+        let (status, detail) = match status {
+            Status::Good(detail)    => ("good", detail),
+            Status::Unknown(detail) => ("unknown key", detail),
+            Status::Bad(detail)     => ("bad", detail),
+            Status::Unsigned        => ("unsigned", String::new()),
+        };
+        total  += 1;
+        failed += usize::from(status != "good");
+        let line = format!("{kind:<6} {name:<12} {status:<11} {detail}");
+        println!("{}", line.trim_end());
+    };
+
+    for (oid, commit) in walk(&repo, show, hide, first)? {
+        for (name, tag) in tags.get(&oid).into_iter().flatten() {
+            report("tag", name, policy.status(tag.signature.as_ref(), tag.time));
+        }
+        report("commit", &oid.short(), policy.status(commit.signature.as_ref(), commit.time));
+    }
+
+    match failed {
+        0 => Ok(()),
+        n => Err(anyhow!("{n} of {total} objects failed verification")),
+    }
+}
+
+impl Policy {
+    fn status(&self, signed: Option<&Signed>, time: i64) -> Status {
+        let Some(signed) = signed else {
+            return Status::Unsigned;
+        };
+
+        let Ok(sig) = SshSig::from_pem(&signed.signature) else {
+            return Status::Bad("not an SSH signature".to_owned());
+        };
+
+        if let Err(error) = check(&sig, "git", &signed.payload) {
+            return Status::Bad(error.to_string());
+        }
+
+        let key = sig.public_key();
+        let fp  = key.fingerprint(HashAlg::Sha256);
+
+        let id = hex::encode(fp);
+
+        if self.revoked.as_ref().is_some_and(|list| list.contains(key)) {
+            return Status::Bad(Error::Revoked.to_string());
+        }
+
+        if self.keychain.as_ref().is_some_and(|keys| keys.revocation(&id).is_some()) {
+            return Status::Bad(Error::Revoked.to_string());
+        }
+
+        if let Some(signers) = &self.signers {
+            let query = Query {
+                key:       key,
+                principal: None,
+                namespace: Some("git"),
+                time:      OffsetDateTime::from_unix_timestamp(time).unwrap_or_else(|_| now()),
+            };
+            let mut rejected = None;
+            for result in signers.find(&query) {
+                match result {
+                    Ok(signer) => return Status::Good(format!("{} {fp}", signer.principals)),
+                    Err(error) => rejected = rejected.or(error.rejected().then_some(error)),
+                }
+            }
+            return match rejected {
+                Some(error) => Status::Bad(error.to_string()),
+                None        => Status::Unknown(fp.to_string()),
+            };
+        }
+
+        if self.keychain.as_ref().is_some_and(|keys| keys.key(&id).is_some()) {
+            return Status::Good(fp.to_string());
+        }
+
+        match self.contacts.as_ref().map(|contacts| contacts.find(&id)) {
+            Some(Ok(contact)) => match contact.trust {
+                Trust::Full     => Status::Good(format!("{} {fp}", contact.principals.join(","))),
+                Trust::Marginal => Status::Good(format!("{} {fp} (marginal trust)", contact.principals.join(","))),
+                Trust::Never    => Status::Bad(Error::Untrusted.to_string()),
+            },
+            _                 => Status::Unknown(fp.to_string()),
+        }
+    }
+}
+
+fn walk<S: System>(repo: &Repository<S>, tip: Oid, hide: Option<Oid>, first: bool) -> Result<Vec<(Oid, Commit)>> {
+    let mut commits = Vec::new();
+    let mut pending = HashMap::new();
+    let mut parents = HashMap::<Oid, Vec<Oid>>::new();
+    let mut hidden  = HashSet::new();
+    let mut queue   = BinaryHeap::new();
+    let mut seen    = HashSet::new();
+
+    for (oid, hide) in [(Some(tip), false), (hide, true)] {
+        let Some(oid) = oid else { continue };
+        if hide {
+            hidden.insert(oid);
+        }
+        if seen.insert(oid) {
+            let commit = repo.commit(&oid)?;
+            queue.push((commit.time, oid));
+            pending.insert(oid, commit);
+        }
+    }
+
+    let mut date = i64::MAX;
+    let mut slop = SLOP;
+
+    while let Some((_, oid)) = queue.pop() {
+        let Some(commit) = pending.remove(&oid) else { continue };
+
+        let hide = hidden.contains(&oid);
+        let next = match first && !hide {
+            true  => &commit.parents[..commit.parents.len().min(1)],
+            false => &commit.parents[..],
+        };
+
+        for parent in next {
+            if hide {
+                let mut stack = vec![*parent];
+                while let Some(oid) = stack.pop() {
+                    if hidden.insert(oid) {
+                        stack.extend(parents.get(&oid).into_iter().flatten());
+                    }
+                }
+            }
+            if seen.insert(*parent) {
+                let next = repo.commit(parent)?;
+                queue.push((next.time, *parent));
+                pending.insert(*parent, next);
+            }
+        }
+
+        parents.insert(oid, next.to_vec());
+
+        if !hide {
+            date = commit.time;
+            commits.push((oid, commit));
+            continue;
+        }
+
+        let visible = queue.iter().any(|(_, oid)| !hidden.contains(oid));
+        slop = match queue.peek() {
+            Some((time, _)) if visible || date <= *time => SLOP,
+            Some(_)                                     => slop - 1,
+            None                                        => 0,
+        };
+
+        if slop == 0 {
+            break;
+        }
+    }
+
+    commits.retain(|(oid, _)| !hidden.contains(oid));
+
+    Ok(commits)
+}
+
+fn repository<S: System>() -> Result<Option<Repository<S>>> {
+    let cwd = env::current_dir()?;
+    Ok(Repository::discover(&cwd))
+}
+
+fn settings(key: &str, program: &str, signers: &Path) -> [(&'static str, String); 6] {
+    [
+        ("user.signingkey",            key.to_owned()),
+        ("gpg.format",                 "ssh".to_owned()),
+        ("gpg.ssh.program",            program.to_owned()),
+        ("gpg.ssh.allowedSignersFile", signers.to_string_lossy().into_owned()),
+        ("commit.gpgsign",             "true".to_owned()),
+        ("tag.gpgsign",                "true".to_owned()),
+    ]
+}
+
+fn previous(name: &str) -> String {
+    format!("{RECORD}.{}", name.replace('.', "-"))
+}
+
+fn allow<S: System>(path: &Path, entry: &str) -> Result<bool> {
+    let data = read::<S>(path)?.unwrap_or_default();
+    let data = String::from_utf8_lossy(&data);
+
+    if data.lines().any(|line| line.trim() == entry) {
+        return Ok(false);
+    }
+
+    let mut data = data.into_owned();
+    if !data.is_empty() && !data.ends_with('\n') {
+        data.push('\n');
+    }
+    data.push_str(entry);
+    data.push('\n');
+
+    write::<S>(path, data.as_bytes())?;
+    Ok(true)
+}
+
+fn disallow<S: System>(path: &Path, entry: &str) -> Result<()> {
+    let Some(data) = read::<S>(path)? else { return Ok(()) };
+    let data = String::from_utf8_lossy(&data);
+
+    let kept = data.lines().filter(|line| line.trim() != entry).map(|line| {
+        format!("{line}\n")
+    }).collect::<String>();
+
+    match kept.len() == data.len() {
+        true  => Ok(()),
+        false => write::<S>(path, kept.as_bytes()),
+    }
+}
+
+fn missing(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<persist::Error>().is_some_and(|error| error.missing())
+}
+
+fn load<S: System>(path: &Path) -> Result<Config> {
+    let data = read::<S>(path)?.unwrap_or_default();
+    Ok(Config::parse(&data))
+}
+
+fn save<S: System>(path: &Path, config: &Config) -> Result<()> {
+    write::<S>(path, config.to_string().as_bytes())
+}
+
+fn read<S: System>(path: &Path) -> Result<Option<Vec<u8>>> {
+    match S::read(&path.to_owned().into()) {
+        Ok(data)                                  => Ok(Some(data)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e)                                    => Err(e).context(path)?,
+    }
+}
+
+fn write<S: System>(path: &Path, data: &[u8]) -> Result<()> {
+    if read::<S>(path)?.is_some() {
+        return Ok(S::sync(path, data).context(path)?);
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        S::mkdir(dir).context(dir)?;
+    }
+    Ok(S::init(path, data).context(path)?)
+}
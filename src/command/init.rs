@@ -1,10 +1,29 @@
+use std::fmt;
+use std::path::PathBuf;
 use anyhow::Result;
+use serde::Serialize;
 use crate::{Signet, System};
 use crate::args::Init;
 use crate::keychain::Password;
+use super::Format;
 
-pub fn init<S: System>(signet: &Signet<S>, args: Init) -> Result<()> {
+#[derive(Debug, Serialize)]
+struct Initialized {
+    root:   PathBuf,
+    secret: bool,
+}
+
+pub fn init<S: System>(signet: &Signet<S>, args: Init, format: Format) -> Result<()> {
     let password = Password::create::<S>(args.secret)?;
     signet.initialize(password)?;
-    Ok(println!("signet initialized!"))
+    format.print(&Initialized {
+        root:   signet.root().to_owned(),
+        secret: args.secret,
+    })
+}
+
+impl fmt::Display for Initialized {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "signet initialized!")
+    }
 }
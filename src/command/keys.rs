@@ -1,69 +1,221 @@
-use anyhow::Result;
+use std::fmt;
+use std::path::PathBuf;
+use anyhow::{anyhow, Result};
 use rand_core::OsRng;
-use ssh_key::{Algorithm, LineEnding, PrivateKey};
-use crate::{Input, Signet, System};
-use crate::args::Keys;
-use crate::persist::read;
+use serde::Serialize;
+use ssh_key::{HashAlg, LineEnding, PrivateKey, PublicKey};
+use time::OffsetDateTime;
+use crate::{Details, Expiry, Input, Key, Keychain, KeyType, Principals, Revoked, Signet, Succession, System, Validity};
+use crate::args::{Allow, Create, Describe, Keys, Revocations, Revoke};
+use crate::keychain::{label, strong, Error};
+use crate::persist::{read, Context};
+use crate::revocation::Krl;
+use crate::timestamp;
+use super::output::{Format, Public};
 
-pub fn keys<S: System>(signet: &Signet<S>, cmd: Keys) -> Result<()> {
+#[derive(Debug, Serialize)]
+struct Changed {
+    action: &'static str,
+    #[serde(flatten)]
+    key:    Public,
+}
+
+#[derive(Debug, Serialize)]
+struct Exported {
+    #[serde(flatten)]
+    key:         Public,
+    private_key: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Published {
+    #[serde(flatten)]
+    key:        Public,
+    public_key: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Allowed {
+    #[serde(flatten)]
+    key:        Public,
+    #[serde(flatten)]
+    principals: Principals,
+}
+
+#[derive(Debug, Serialize)]
+struct Signers {
+    signers: Vec<Signer>,
+}
+
+#[derive(Debug, Serialize)]
+struct Signer {
+    #[serde(flatten)]
+    key:        Public,
+    #[serde(flatten)]
+    principals: Principals,
+    public_key: String,
+    line:       String,
+}
+
+#[derive(Debug, Serialize)]
+struct Rotated {
+    #[serde(flatten)]
+    key:        Public,
+    successor:  Public,
+    succession: Succession,
+}
+
+#[derive(Debug, Serialize)]
+struct Statement {
+    #[serde(flatten)]
+    succession: Succession,
+}
+
+#[derive(Debug, Serialize)]
+struct Revocation {
+    keys: Vec<RevokedKey>,
+    krl:  Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize)]
+struct RevokedKey {
+    #[serde(flatten)]
+    key:        Public,
+    keychain:   String,
+    revoked:    String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    reason:     String,
+    public_key: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Configured {
+    keychain: String,
+    expiry:   Expiry,
+}
+
+#[derive(Debug, Serialize)]
+struct Listing {
+    keychain: String,
+    keys:     Vec<Listed>,
+}
+
+#[derive(Debug, Serialize)]
+struct Listed {
+    #[serde(flatten)]
+    key:         Public,
+    #[serde(flatten)]
+    details:     Details,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    principals:  Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    valid_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires:     Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    successor:   Option<String>,
+    lifetime:    String,
+}
+
+pub fn keys<S: System>(signet: &Signet<S>, cmd: Keys, format: Format) -> Result<()> {
     match cmd {
-        Keys::Create      => create(signet),
-        Keys::Delete(key) => delete(signet, &key),
-        Keys::Export(key) => export(signet, &key),
-        Keys::Import(key) => import(signet, &key),
-        Keys::Public(key) => public(signet, &key),
-        Keys::List        => list(signet),
+        Keys::Create(cmd)    => create(signet, cmd, format),
+        Keys::Delete(key)    => delete(signet, &key, format),
+        Keys::Export(key)    => export(signet, &key, format),
+        Keys::Import(key, d) => import(signet, &key, d, format),
+        Keys::Edit(key, d)   => edit(signet, &key, d, format),
+        Keys::Public(key)    => public(signet, &key, format),
+        Keys::Allow(args)    => allow(signet, args, format),
+        Keys::Signers(ks)    => signers(signet, &ks, format),
+        Keys::Expiry(exp)    => expiry(signet, exp, format),
+        Keys::Rotate(key)    => rotate(signet, &key, format),
+        Keys::Statement(key) => statement(signet, &key, format),
+        Keys::Revoke(args)   => revoke(signet, args, format),
+        Keys::Revocations(r) => revocations(signet, r, format),
+        Keys::List           => list(signet, format),
     }
 }
 
-pub fn create<S: System>(signet: &Signet<S>) -> Result<()> {
+pub fn create<S: System>(signet: &Signet<S>, args: Create, format: Format) -> Result<()> {
+    let Create { kind, after, expires, describe: args } = args;
+
     let mut keychain = signet.current()?;
 
+    let now      = timestamp::now();
+    let validity = Validity {
+        valid_after: after.map(|time| match timestamp::parse(&time) {
+            Some(time) => Ok(timestamp::compact(time)),
+            None       => Err(anyhow!("invalid time '{time}'")),
+        }).transpose()?,
+        expires:     expires.map(|time| match timestamp::expiry(&time, now) {
+            Some(time) => Ok(timestamp::compact(time)),
+            None       => Err(anyhow!("invalid expiry '{time}'")),
+        }).transpose()?,
+    };
+    if let (Some(after), Some(until)) = (validity.after(), validity.until()) {
+        if until <= after {
+            return Err(anyhow!("expiry {} is not after valid-after {}", timestamp::compact(until), timestamp::compact(after)));
+        }
+    }
+    let details  = Details {
+        created: Some(timestamp::compact(now)),
+        ..Details::default()
+    };
+
     let password = keychain.password();
     let password = password.lookup::<S>()?;
 
-    let alg = Algorithm::Ed25519;
-    let key = PrivateKey::random(OsRng, alg)?;
+    let key = kind.generate()?;
 
-    let key = key.encrypt(OsRng, password)?;
-    let id  = keychain.add(key);
+    let key    = key.encrypt(OsRng, password)?;
+    let public = Public::from(key.public_key());
+    let id     = keychain.add(key);
+    if !validity.is_empty() {
+        keychain.limit(&id, validity);
+    }
+    describe(&mut keychain, &id, details, args)?;
     signet.sync(&keychain)?;
 
-    Ok(println!("created key {id}"))
+    format.print(&Changed { action: "created", key: public })
 }
 
-pub fn delete<S: System>(signet: &Signet<S>, key: &str) -> Result<()> {
+pub fn delete<S: System>(signet: &Signet<S>, key: &str, format: Format) -> Result<()> {
     let mut keychain = signet.current()?;
 
-    let key = keychain.find(key)?;
-    let id  = key.id();
+    let key    = keychain.find(&selector::<S>(key)?)?;
+    let id     = key.id();
+    let public = Public::from(key.public_key());
 
     keychain.delete(&id);
     signet.sync(&keychain)?;
 
-    Ok(println!("deleted key {id}"))
+    format.print(&Changed { action: "deleted", key: public })
 }
 
-pub fn export<S: System>(signet: &Signet<S>, key: &str) -> Result<()> {
+pub fn export<S: System>(signet: &Signet<S>, key: &str, format: Format) -> Result<()> {
     let keychain = signet.current()?;
     let password = keychain.password();
     let password = password.lookup::<S>()?;
 
-    let key = keychain.find(key)?;
+    let key = keychain.find(&selector::<S>(key)?)?;
     let key = key.decrypt(password)?;
 
     let password = S::prompt("export password: ")?;
     let exported = key.encrypt(OsRng, password)?;
-    let exported = &*exported.to_openssh(LineEnding::default())?;
+    let exported = exported.to_openssh(LineEnding::default())?;
 
-    Ok(print!("{exported}"))
+    format.print(&Exported {
+        key:         Public::from(key.public_key()),
+        private_key: exported.to_string(),
+    })
 }
 
-pub fn import<S: System>(signet: &Signet<S>, key: &Input) -> Result<()> {
+pub fn import<S: System>(signet: &Signet<S>, key: &Input, args: Describe, format: Format) -> Result<()> {
     let mut keychain = signet.current()?;
 
     let key = read::<S>(key)?;
     let key = PrivateKey::from_openssh(key)?;
+    strong(key.public_key().key_data())?;
 
     let password = keychain.password();
     let password = password.lookup::<S>()?;
@@ -71,36 +223,450 @@ pub fn import<S: System>(signet: &Signet<S>, key: &Input) -> Result<()> {
     let key = match key.is_encrypted() {
         true  => decrypt::<S>(key)?,
         false => key,
-    }.encrypt(OsRng, password)?;
+    };
 
-    let id = keychain.add(key);
+    let details = Details {
+        comment: key.comment().to_owned(),
+        created: Some(timestamp::compact(timestamp::now())),
+        ..Details::default()
+    };
+
+    let key    = key.encrypt(OsRng, password)?;
+    let public = Public::from(key.public_key());
+    let id     = keychain.add(key);
+    describe(&mut keychain, &id, details, args)?;
+    signet.sync(&keychain)?;
+
+    format.print(&Changed { action: "imported", key: public })
+}
+
+pub fn edit<S: System>(signet: &Signet<S>, key: &str, args: Describe, format: Format) -> Result<()> {
+    let mut keychain = signet.current()?;
+
+    let key     = keychain.find(&selector::<S>(key)?)?;
+    let id      = key.id();
+    let public  = Public::from(key.public_key());
+    let details = keychain.details(&id).cloned().unwrap_or_default();
+
+    describe(&mut keychain, &id, details, args)?;
     signet.sync(&keychain)?;
 
-    Ok(println!("imported key {id}"))
+    format.print(&Changed { action: "updated", key: public })
 }
 
-pub fn public<S: System>(signet: &Signet<S>, key: &str) -> Result<()> {
+pub fn public<S: System>(signet: &Signet<S>, key: &str, format: Format) -> Result<()> {
     let keychain = signet.current()?;
 
-    let key = keychain.find(key)?;
-    let key = key.public_key().to_string();
+    let key = keychain.find(&selector::<S>(key)?)?.public_key();
 
-    Ok(println!("{key}"))
+    format.print(&Published {
+        key:        Public::from(key),
+        public_key: key.to_string(),
+    })
 }
 
-pub fn list<S: System>(signet: &Signet<S>) -> Result<()> {
+pub fn allow<S: System>(signet: &Signet<S>, args: Allow, format: Format) -> Result<()> {
+    let Allow { key, principals, namespaces, after, before } = args;
+
+    let mut keychain = signet.current()?;
+
+    let key    = keychain.find(&selector::<S>(&key)?)?;
+    let id     = key.id();
+    let public = Public::from(key.public_key());
+
+    let list = |str: &str| str.split(',').map(str::to_owned).collect::<Vec<_>>();
+
+    let principals = list(&principals);
+    if principals.iter().any(|p| p.is_empty() || p.contains(char::is_whitespace)) {
+        return Err(anyhow!("invalid principals"));
+    }
+
+    for time in [&after, &before].into_iter().flatten() {
+        timestamp::parse(time).ok_or_else(|| anyhow!("invalid time '{time}'"))?;
+    }
+
+    let principals = Principals {
+        principals:   principals,
+        namespaces:   namespaces.as_deref().map(list).unwrap_or_default(),
+        valid_after:  after,
+        valid_before: before,
+    };
+
+    keychain.allow(&id, principals.clone());
+    signet.sync(&keychain)?;
+
+    format.print(&Allowed { key: public, principals })
+}
+
+pub fn signers<S: System>(signet: &Signet<S>, names: &[String], format: Format) -> Result<()> {
+    let keystore = signet.keystore()?;
+    let names    = match names {
+        []    => vec![keystore.current()?.to_owned()],
+        names => names.to_vec(),
+    };
+
+    let mut signers = Vec::new();
+    for name in names {
+        let keychain = signet.keychain(&name)?;
+        for key in keychain.keys() {
+            if let Some(principals) = keychain.principals(&key.id()) {
+                signers.push(Signer {
+                    key:        Public::from(key.public_key()),
+                    principals: principals.clone(),
+                    public_key: key.public_key().to_string(),
+                    line:       signer(key, principals),
+                });
+            }
+        }
+    }
+
+    format.print(&Signers { signers })
+}
+
+pub fn list<S: System>(signet: &Signet<S>, format: Format) -> Result<()> {
     let keystore = signet.keystore()?;
     let current  = keystore.current()?;
     let keychain = signet.keychain(current)?;
 
-    let list = keychain.keys().map(|key| {
-        format!("{:>66}", key.id())
-    }).collect::<Vec<_>>().join("\n");
+    let now  = timestamp::now();
+    let keys = keychain.keys().map(|key| {
+        let validity  = keychain.validity(&key.id()).cloned().unwrap_or_default();
+        let details   = keychain.details(&key.id()).cloned().unwrap_or_default();
+        let successor = keychain.successor(&key.id()).map(|succession| {
+            Public::from(&succession.new).id
+        });
+        let lifetime  = match (keychain.revocation(&key.id()), &successor) {
+            (Some(revoked), _) => match revoked.reason.as_str() {
+                ""     => "revoked".to_owned(),
+                reason => format!("revoked: {reason}"),
+            },
+            (None, Some(id))   => format!("retired, succeeded by {id}"),
+            (None, None)       => lifetime(&validity, now),
+        };
+        let signer    = keychain.principals(&key.id()).cloned().unwrap_or_default();
+        Listed {
+            key:         Public::from(key.public_key()),
+            details:     details,
+            principals:  signer.principals,
+            valid_after: validity.valid_after,
+            expires:     validity.expires,
+            successor:   successor,
+            lifetime:    lifetime,
+        }
+    }).collect();
+
+    format.print(&Listing { keychain: current.to_owned(), keys })
+}
+
+pub fn rotate<S: System>(signet: &Signet<S>, key: &str, format: Format) -> Result<()> {
+    let mut keychain = signet.current()?;
+
+    let key = keychain.find(&selector::<S>(key)?)?;
+    let id  = key.id();
+    keychain.usable(&id)?;
+
+    let password = keychain.password();
+    let password = password.lookup::<S>()?;
+
+    let old = key.decrypt(password.as_bytes())?;
+    let new = KeyType::of(old.public_key().key_data()).generate()?;
+    let now = timestamp::now();
+
+    let succession = Succession::sign(&old, &new, now)?;
+
+    let new       = new.encrypt(OsRng, password)?;
+    let successor = Public::from(new.public_key());
+    let next      = keychain.add(new);
+    let details   = keychain.details(&id).cloned().unwrap_or_default();
+    keychain.describe(&next, Details {
+        created: Some(timestamp::compact(now)),
+        ..details
+    });
+    if let Some(principals) = keychain.principals(&id).cloned() {
+        keychain.allow(&next, principals);
+    }
+    keychain.retire(&id, succession.clone());
+    signet.sync(&keychain)?;
+
+    format.print(&Rotated {
+        key:        Public::from(old.public_key()),
+        successor:  successor,
+        succession: succession,
+    })
+}
+
+pub fn statement<S: System>(signet: &Signet<S>, key: &str, format: Format) -> Result<()> {
+    let keychain = signet.current()?;
+
+    let key        = keychain.find(&selector::<S>(key)?)?;
+    let succession = keychain.successor(&key.id()).cloned();
+    let succession = succession.ok_or_else(|| anyhow!("key {} is not retired", key.id()))?;
+
+    format.print(&Statement { succession })
+}
+
+pub fn revoke<S: System>(signet: &Signet<S>, args: Revoke, format: Format) -> Result<()> {
+    let Revoke { key, reason } = args;
 
-    Ok(println!("keychain '{current}':\n{list}"))
+    let mut keychain = signet.current()?;
+
+    let key    = keychain.find(&selector::<S>(&key)?)?;
+    let id     = key.id();
+    let public = Public::from(key.public_key());
+
+    if let Some(revoked) = keychain.revocation(&id) {
+        return Err(Error::KeyRevoked(revoked.reason.clone()).into());
+    }
+
+    keychain.revoke(&id, Revoked {
+        revoked: timestamp::compact(timestamp::now()),
+        reason:  reason,
+        key:     Some(key.public_key().clone()),
+    });
+    signet.sync(&keychain)?;
+
+    format.print(&Changed { action: "revoked", key: public })
+}
+
+pub fn revocations<S: System>(signet: &Signet<S>, args: Revocations, format: Format) -> Result<()> {
+    let Revocations { keychains: names, krl: path } = args;
+
+    let keystore = signet.keystore()?;
+    let names    = match &names[..] {
+        []    => vec![keystore.current()?.to_owned()],
+        names => names.to_vec(),
+    };
+
+    let mut keys = Vec::new();
+    let mut list = Krl::default();
+    for name in names {
+        let keychain = signet.keychain(&name)?;
+        for (id, revoked) in keychain.revocations() {
+            let key = match (&revoked.key, keychain.key(id)) {
+                (Some(key), _)      => key.clone(),
+                (None, Some(entry)) => entry.public_key().clone(),
+                (None, None)        => continue,
+            };
+            list.keys.push(key.key_data().clone());
+            keys.push(RevokedKey {
+                key:        Public::from(&key),
+                keychain:   name.clone(),
+                revoked:    revoked.revoked.clone(),
+                reason:     revoked.reason.clone(),
+                public_key: key.to_string(),
+            });
+        }
+    }
+
+    if let Some(path) = &path {
+        let now = timestamp::now().unix_timestamp() as u64;
+        let krl = Krl {
+            version:   now,
+            generated: now,
+            comment:   "signet".to_owned(),
+            ..list
+        };
+        S::write(path, &krl.encode()?).context(path)?;
+    }
+
+    format.print(&Revocation { keys, krl: path })
+}
+
+pub fn expiry<S: System>(signet: &Signet<S>, expiry: Expiry, format: Format) -> Result<()> {
+    let keystore = signet.keystore()?;
+    let current  = keystore.current()?;
+
+    let mut keychain = signet.keychain(current)?;
+    keychain.expiry(expiry);
+    signet.sync(&keychain)?;
+
+    format.print(&Configured { keychain: current.to_owned(), expiry })
+}
+
+pub fn selector<S: System>(key: &str) -> Result<String> {
+    if !key.contains(std::path::is_separator) && !key.ends_with(".pub") {
+        return Ok(key.to_owned());
+    }
+
+    let path = PathBuf::from(key);
+    match S::read(&path.clone().into()) {
+        Ok(data) => fingerprint(&data).ok_or_else(|| {
+            anyhow!("{}: not a public or private key", path.display())
+        }),
+        Err(_)   => Ok(key.to_owned()),
+    }
+}
+
+fn fingerprint(data: &[u8]) -> Option<String> {
+    let data = std::str::from_utf8(data).ok()?.trim();
+    let key  = match PublicKey::from_openssh(data) {
+        Ok(key) => key,
+        Err(_)  => PrivateKey::from_openssh(data).ok()?.public_key().clone(),
+    };
+    Some(hex::encode(key.fingerprint(HashAlg::Sha256)))
+}
+
+fn lifetime(validity: &Validity, now: OffsetDateTime) -> String {
+    match (validity.after(), validity.until()) {
+        (Some(after), _) if now < after  => format!("valid in {}", timestamp::span(after - now)),
+        (_, Some(until)) if now >= until => format!("expired {} ago", timestamp::span(now - until)),
+        (_, Some(until))                 => format!("expires in {}", timestamp::span(until - now)),
+        (_, None)                        => "no expiry".to_owned(),
+    }
+}
+
+fn describe(keychain: &mut Keychain, id: &str, details: Details, args: Describe) -> Result<()> {
+    let Describe { label, comment, principals } = args;
+
+    if let Some(label) = &label {
+        self::label(label)?;
+    }
+
+    match principals {
+        Some(principals) if principals.is_empty() => keychain.disallow(id),
+        Some(principals)                          => {
+            if principals.iter().any(|p| p.is_empty() || p.contains(char::is_whitespace)) {
+                return Err(anyhow!("invalid principals"));
+            }
+            let signer = keychain.principals(id).cloned().unwrap_or_default();
+            keychain.allow(id, Principals { principals, ..signer });
+        },
+        None                                      => (),
+    }
+
+    keychain.describe(id, Details {
+        label:   label.unwrap_or(details.label),
+        comment: comment.unwrap_or(details.comment),
+        ..details
+    });
+
+    Ok(())
+}
+
+fn signer(key: &Key, principals: &Principals) -> String {
+    let mut options = Vec::new();
+    if !principals.namespaces.is_empty() {
+        options.push(format!("namespaces=\"{}\"", principals.namespaces.join(",")));
+    }
+    if let Some(after) = &principals.valid_after {
+        options.push(format!("valid-after=\"{after}\""));
+    }
+    if let Some(before) = &principals.valid_before {
+        options.push(format!("valid-before=\"{before}\""));
+    }
+
+    let names = principals.principals.join(",");
+    let key   = key.public_key().to_string();
+    match options.is_empty() {
+        true  => format!("{names} {key}"),
+        false => format!("{names} {} {key}", options.join(",")),
+    }
 }
 
 fn decrypt<S: System>(key: PrivateKey) -> Result<PrivateKey> {
     let password = S::prompt("key password: ")?;
     Ok(key.decrypt(password)?)
 }
+
+impl fmt::Display for Changed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} key {}", self.action, self.key.id)
+    }
+}
+
+impl fmt::Display for Exported {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.private_key.trim_end())
+    }
+}
+
+impl fmt::Display for Published {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.public_key)
+    }
+}
+
+impl fmt::Display for Allowed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "updated principals for key {}", self.key.id)
+    }
+}
+
+impl fmt::Display for Signers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines = self.signers.iter().map(|signer| signer.line.as_str());
+        write!(f, "{}", lines.collect::<Vec<_>>().join("\n"))
+    }
+}
+
+impl fmt::Display for Rotated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "rotated key {}, succeeded by {}", self.key.id, self.successor.id)
+    }
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let yaml = serde_yaml::to_string(&self.succession).map_err(|_| fmt::Error)?;
+        write!(f, "{}", yaml.trim_end())
+    }
+}
+
+impl fmt::Display for Revocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines = self.keys.iter().map(|key| key.public_key.as_str());
+        write!(f, "{}", lines.collect::<Vec<_>>().join("\n"))
+    }
+}
+
+impl fmt::Display for Configured {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let action = match self.expiry {
+            Expiry::Refuse => "refuses",
+            Expiry::Warn   => "warns about",
+        };
+        write!(f, "keychain '{}' {action} signing with expired keys", self.keychain)
+    }
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cell = |str: &str| match str {
+            ""  => "-".to_owned(),
+            str => str.to_owned(),
+        };
+
+        let header = ["ID", "LABEL", "ALGORITHM", "CREATED", "PRINCIPALS", "STATUS", "COMMENT"];
+        let mut rows = vec![header.map(str::to_owned)];
+        rows.extend(self.keys.iter().map(|Listed { key, details, principals, lifetime, .. }| {
+            let created = details.created.as_deref().and_then(timestamp::parse);
+            let created = created.map(timestamp::format).unwrap_or_default();
+            [
+                key.id.chars().take(16).collect(),
+                cell(&details.label),
+                key.algorithm.clone(),
+                cell(created.split('T').next().unwrap_or_default()),
+                cell(&principals.join(",")),
+                lifetime.clone(),
+                cell(&details.comment),
+            ]
+        }));
+
+        let widths = header.map(|_| 0);
+        let widths = rows.iter().fold(widths, |mut widths, row| {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+            widths
+        });
+
+        write!(f, "keychain '{}':", self.keychain)?;
+        for row in rows {
+            let line = row.iter().zip(widths).map(|(cell, width)| {
+                format!("{cell:<width$}")
+            }).collect::<Vec<_>>().join("  ");
+            write!(f, "\n{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
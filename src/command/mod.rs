@@ -1,9 +1,22 @@
+pub use compat::compat;
+pub use contacts::contacts;
+pub use error::Kind;
+pub use git::git;
 pub use init::init;
 pub use keys::keys;
+pub use output::{failure, Format};
+pub use passwd::passwd;
+pub use sign::countersign;
 pub use sign::sign;
 pub use verify::verify;
 
+mod compat;
+mod contacts;
+mod error;
+mod git;
 mod init;
 mod keys;
+mod output;
+mod passwd;
 mod sign;
 mod verify;
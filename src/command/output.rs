@@ -0,0 +1,76 @@
+use std::fmt;
+use std::process::ExitCode;
+use anyhow::{Error, Result};
+use serde::Serialize;
+use ssh_key::{HashAlg, PublicKey};
+use super::Kind;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Public {
+    pub id:          String,
+    pub fingerprint: String,
+    pub algorithm:   String,
+}
+
+#[derive(Debug, Serialize)]
+struct Failure {
+    error: Cause,
+}
+
+#[derive(Debug, Serialize)]
+struct Cause {
+    kind:    Kind,
+    code:    u8,
+    message: String,
+    causes:  Vec<String>,
+}
+
+impl Format {
+    pub fn print<T: Serialize + fmt::Display>(self, value: &T) -> Result<()> {
+        match self {
+            Self::Text => match value.to_string() {
+                text if text.is_empty() => (),
+                text                    => println!("{text}"),
+            },
+            Self::Json => println!("{}", serde_json::to_string(value)?),
+        }
+        Ok(())
+    }
+}
+
+pub fn failure(error: &Error, format: Format) -> ExitCode {
+    let kind = Kind::of(error);
+
+    let failure = Failure {
+        error: Cause {
+            kind:    kind,
+            code:    kind.code(),
+            message: error.to_string(),
+            causes:  error.chain().skip(1).map(ToString::to_string).collect(),
+        },
+    };
+
+    match (format, serde_json::to_string(&failure)) {
+        (Format::Json, Ok(json)) => eprintln!("{json}"),
+        _                        => eprintln!("Error: {error:?}"),
+    }
+
+    kind.into()
+}
+
+impl From<&PublicKey> for Public {
+    fn from(key: &PublicKey) -> Self {
+        let fingerprint = key.fingerprint(HashAlg::Sha256);
+        Self {
+            id:          hex::encode(fingerprint),
+            fingerprint: fingerprint.to_string(),
+            algorithm:   key.algorithm().as_str().to_owned(),
+        }
+    }
+}
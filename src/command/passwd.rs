@@ -0,0 +1,66 @@
+use std::fmt;
+use anyhow::Result;
+use rand_core::OsRng;
+use serde::Serialize;
+use crate::{Key, Signet, System};
+use crate::args::Passwd;
+use crate::keychain::Password;
+use super::Format;
+
+#[derive(Debug, Serialize)]
+struct Changed {
+    keychain: String,
+    keys:     usize,
+    secret:   bool,
+}
+
+pub fn passwd<S: System>(signet: &Signet<S>, args: Passwd, format: Format) -> Result<()> {
+    let mut keychain = signet.current()?;
+
+    let secret   = args.secret.unwrap_or_else(|| keychain.password().is_secret());
+    let password = keychain.password().lookup::<S>()?;
+
+    let keys = keychain.keys().map(|key| {
+        Ok(key.decrypt(password.as_bytes())?)
+    }).collect::<Result<Vec<_>>>()?;
+
+    let (password, new) = Password::prompt::<S>(secret)?;
+
+    let keys = keys.into_iter().map(|key| {
+        Ok(Key::from(key.encrypt(OsRng, new.as_bytes())?))
+    }).collect::<Result<Vec<_>>>();
+
+    let keys = match keys {
+        Ok(keys)   => keys,
+        Err(error) => return Err(abandon(&password, error)),
+    };
+
+    let count = keys.len();
+    let old   = keychain.rekey(password, keys);
+    if let Err(error) = signet.sync(&keychain) {
+        return Err(abandon(keychain.password(), error));
+    }
+
+    if let Err(error) = old.discard() {
+        eprintln!("warning: old password not removed from keyring: {error}");
+    }
+
+    format.print(&Changed {
+        keychain: keychain.metadata().identity.clone(),
+        keys:     count,
+        secret:   secret,
+    })
+}
+
+fn abandon(password: &Password, error: anyhow::Error) -> anyhow::Error {
+    if let Err(error) = password.discard() {
+        eprintln!("warning: new password not removed from keyring: {error}");
+    }
+    error
+}
+
+impl fmt::Display for Changed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "password changed, re-encrypted {} keys in keychain '{}'", self.keys, self.keychain)
+    }
+}
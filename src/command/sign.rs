@@ -1,38 +1,143 @@
+use std::fmt;
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
-use anyhow::Result;
-use ssh_key::{HashAlg, LineEnding};
-use crate::{Input, Signet, System};
-use crate::args::Sign;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use ssh_key::{Algorithm, HashAlg, LineEnding};
+use ssh_key::public::KeyData;
+use crate::{Expiry, Input, Signet, System};
+use crate::args::{Countersign, Sign};
 use crate::persist::{read, Context};
+use crate::timestamp;
+use super::keys::selector;
+use super::output::{Format, Public};
+use super::verify::{bundle, countersigned, COUNTERSIGN};
 
-pub fn sign<S: System>(signet: &Signet<S>, args: Sign) -> Result<()> {
-    let Sign { key, ns, data } = args;
+#[derive(Debug, Serialize)]
+struct Signed {
+    #[serde(flatten)]
+    key:       Public,
+    namespace: String,
+    hash:      &'static str,
+    signature: Option<PathBuf>,
+    pem:       String,
+}
+
+pub fn sign<S: System>(signet: &Signet<S>, args: Sign, format: Format) -> Result<()> {
+    let Sign { key, ns, hash, append, data } = args;
+
+    let bundle = match (append, &data) {
+        (true, Input::File(path)) => Some(self::append(path, ".sig")),
+        (true, Input::Stdin)      => return Err(anyhow!("cannot append signature when signing stdin")),
+        (false, _)                => None,
+    };
+
+    let msg = read::<S>(&data)?;
+    let (key, sig) = signature(signet, &key, &ns, hash, &msg, bundle.as_deref())?;
+
+    let output = match (&data, format) {
+        (Input::Stdin, Format::Json) => None,
+        (data, _)                    => Some(write::<S>(data, sig.as_bytes())?),
+    };
+
+    format.print(&Signed {
+        key:       key,
+        namespace: ns,
+        hash:      hash.as_str(),
+        signature: output,
+        pem:       sig,
+    })
+}
+
+pub fn countersign<S: System>(signet: &Signet<S>, args: Countersign) -> Result<()> {
+    let Countersign { key, hash, sig } = args;
+
+    let data = read::<S>(&sig.clone().into())?;
+    let msg  = countersigned(&data).map_err(|e| anyhow!("{}: {e}", sig.display()))?;
+
+    let output = append(&sig, ".csig");
+    let (_, sig) = signature(signet, &key, COUNTERSIGN, hash, &msg, Some(&output))?;
+
+    Ok(S::write(&output, sig.as_bytes()).context(&output)?)
+}
 
+fn signature<S: System>(signet: &Signet<S>, key: &str, ns: &str, hash: HashAlg, msg: &[u8], bundle: Option<&Path>) -> Result<(Public, String)> {
     let keychain = signet.current()?;
     let password = keychain.password();
-    let key      = keychain.find(&key)?;
-    let msg      = read::<S>(&data)?;
+    let key      = keychain.find(&selector::<S>(key)?)?;
+
+    keychain.usable(&key.id())?;
+
+    let validity = keychain.validity(&key.id()).map(|validity| {
+        validity.check(timestamp::now())
+    });
+
+    match (validity, keychain.metadata().expiry) {
+        (Some(Err(error)), Expiry::Refuse) => return Err(error.into()),
+        (Some(Err(error)), Expiry::Warn)   => eprintln!("warning: {error}"),
+        _                                  => (),
+    }
+
+    let bundle   = match bundle {
+        Some(path) => existing::<S>(path, key.public_key().key_data(), ns)?,
+        None       => String::new(),
+    };
     let password = password.lookup::<S>()?;
 
     let key = key.decrypt(password)?;
-    let sig = key.sign(&ns, HashAlg::Sha256, &msg)?;
+    let sig = key.sign(ns, hash, msg)?;
+
+    if let Algorithm::Rsa { hash: alg } = sig.algorithm() {
+        if alg != Some(HashAlg::Sha512) {
+            return Err(anyhow!("unsupported RSA signature algorithm {}", sig.algorithm()));
+        }
+    }
+
     let sig = sig.to_pem(LineEnding::default())?;
 
-    write::<S>(&data, sig.as_bytes())?;
+    Ok((Public::from(key.public_key()), format!("{bundle}{sig}")))
+}
+
+fn existing<S: System>(path: &Path, key: &KeyData, ns: &str) -> Result<String> {
+    let data = match S::read(&path.to_owned().into()) {
+        Ok(data)                                  => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(String::new()),
+        Err(e)                                    => Err(e).context(path)?,
+    };
 
-    Ok(())
+    for sig in bundle(&data).map_err(|e| anyhow!("{}: {e}", path.display()))? {
+        if sig.namespace() != ns {
+            return Err(anyhow!("{}: signed with namespace '{}'", path.display(), sig.namespace()));
+        }
+        if sig.public_key() == key {
+            return Err(anyhow!("{}: already signed by this key", path.display()));
+        }
+    }
+
+    let mut data = String::from_utf8(data)?;
+    if !data.ends_with('\n') {
+        data.push('\n');
+    }
+    Ok(data)
 }
 
-fn write<S: System>(input: &Input, data: &[u8]) -> Result<()> {
+fn write<S: System>(input: &Input, data: &[u8]) -> Result<PathBuf> {
     let output = match input {
         Input::File(path) => append(path, ".sig"),
         Input::Stdin      => "/dev/stdout".into(),
     };
-    Ok(S::write(&output, data).context(&output)?)
+    S::write(&output, data).context(&output)?;
+    Ok(output)
 }
 
-fn append(path: &Path, suffix: &str) -> PathBuf {
+pub fn append(path: &Path, suffix: &str) -> PathBuf {
     let mut filename = path.file_name().unwrap_or_default().to_owned();
     filename.push(suffix);
     path.with_file_name(filename)
 }
+
+impl fmt::Display for Signed {
+    fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+        Ok(())
+    }
+}
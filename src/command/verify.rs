@@ -1,23 +1,533 @@
-use anyhow::Result;
-use ssh_key::{HashAlg, SshSig};
-use crate::{Signet, System};
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Component, Path};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use ssh_key::{HashAlg, PublicKey};
+use ssh_encoding::Encode;
+use ssh_key::public::KeyData;
+use time::OffsetDateTime;
+use crate::{Contact, Contacts, Input, Keychain, Policy, Revocations, Signature, Signet, Succession, System, Trust};
 use crate::args::Verify;
-use crate::persist::read;
+use crate::persist::{read, Context};
+use crate::signers::matches;
+use crate::sshsig::SshSig;
+use crate::timestamp;
+use super::output::{Format, Public};
+use super::sign::append;
 
-pub fn verify<S: System>(signet: &Signet<S>, args: Verify) -> Result<()> {
-    let Verify { sig, ns, data } = args;
+pub const COUNTERSIGN: &str = "signet-countersign";
 
-    let msg = read::<S>(&data)?;
-    let sig = read::<S>(&sig.into())?;
-    let sig = SshSig::from_pem(sig)?;
+const END: &str = "-----END SSH SIGNATURE-----";
 
+#[derive(Debug)]
+pub enum Error {
+    Namespace,
+    Signature,
+    Revoked,
+    Untrusted,
+    Policy,
+    Threshold(usize, usize),
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    valid:             bool,
+    namespace:         String,
+    signatures:        Vec<Entry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    threshold:         Option<Threshold>,
+    countersignatures: Vec<Vec<Entry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error:             Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Threshold {
+    found:    usize,
+    required: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    #[serde(flatten)]
+    key:          Public,
+    status:       &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    security_key: Option<SecurityKey>,
+    #[serde(flatten)]
+    signer:       Option<Signer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error:        Option<String>,
+    #[serde(skip)]
+    action:       &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct SecurityKey {
+    flags:        u8,
+    counter:      u32,
+    presence:     bool,
+    verification: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Signer {
+    principals: Vec<String>,
+    trust:      &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    succeeds:   Vec<String>,
+    rules:      Vec<Rule>,
+}
+
+#[derive(Debug, Serialize)]
+struct Rule {
+    rule:   String,
+    passed: bool,
+    reason: String,
+}
+
+pub fn verify<S: System>(signet: &Signet<S>, args: Verify, format: Format) -> Result<()> {
+    let mut report = Report {
+        valid:             false,
+        namespace:         args.ns.clone(),
+        signatures:        Vec::new(),
+        threshold:         None,
+        countersignatures: Vec::new(),
+        error:             None,
+    };
+
+    let result = run(signet, args, &mut report);
+
+    report.valid = result.is_ok();
+    report.error = result.as_ref().err().map(ToString::to_string);
+    format.print(&report)?;
+
+    result
+}
+
+fn run<S: System>(signet: &Signet<S>, args: Verify, report: &mut Report) -> Result<()> {
+    let Verify { sig, ns, revoked, threshold, signers, time, data } = args;
+
+    let time = time.map(|time| match timestamp::parse(&time) {
+        Some(time) => Ok(time),
+        None       => Err(anyhow!("invalid time '{time}'")),
+    }).transpose()?;
+
+    let msg  = read::<S>(&data)?;
+    let sigs = read::<S>(&sig.clone().into())?;
+    let sigs = bundle(&sigs).map_err(|e| anyhow!("{}: {e}", sig.display()))?;
+
+    if let Some(required) = threshold {
+        if required < 1 || required > sigs.len() {
+            return Err(anyhow!("threshold must be between 1 and {}, the signatures in {}", sigs.len(), sig.display()));
+        }
+    }
+
+    let path = match &data {
+        Input::File(path) => Some(relative(path)?),
+        Input::Stdin      => None,
+    };
+
+    let revoked = revoked.map(|path| revocations::<S>(&path)).transpose()?;
+
+    let policy = signet.policy()?;
+    if path.is_none() && policy.as_ref().is_some_and(|policy| policy.paths(&ns)) {
+        return Err(anyhow!("policy has path rules for namespace {ns}, signed data must be given as a file"));
+    }
+
+    let verify = |sig: &SshSig, ns: &str, msg: &[u8], path: Option<&str>| {
+        let signer = signer(signet, sig, ns, msg, policy.as_ref(), revoked.as_ref(), path)?;
+        if let Some(time) = time {
+            valid(signet, sig.public_key(), time)?;
+        }
+        Ok::<_, anyhow::Error>(signer)
+    };
+
+    if let ([one], None, None) = (&sigs[..], threshold, &signers) {
+        let signer = verify(one, &ns, &msg, path.as_deref())?;
+        let passed = signer.passed();
+        report.signatures.push(Entry::good(one, signer, "good signature from"));
+        if !passed {
+            return Err(Error::Policy.into());
+        }
+        return countersignatures::<S>(&sig, verify, report);
+    }
+
+    let group = match (&signers, &policy) {
+        (Some(name), Some(policy)) => Some((name, policy.group(name)?)),
+        (Some(name), None)         => Err(anyhow!("no policy defines group '{name}'"))?,
+        (None, _)                  => None,
+    };
+
+    let mut found = HashSet::new();
+    for sig in &sigs {
+        let signer = match verify(sig, &ns, &msg, path.as_deref()) {
+            Ok(signer) => signer,
+            Err(error) => {
+                report.signatures.push(Entry::rejected(sig, error.to_string()));
+                continue;
+            }
+        };
+
+        if let Some((name, members)) = group {
+            let member = signer.principals.iter().any(|principal| {
+                members.iter().any(|pattern| matches(principal, pattern))
+            });
+            if !member {
+                report.signatures.push(Entry::rejected(sig, format!("not in group {name}")));
+                continue;
+            }
+        }
+
+        if signer.passed() && signer.trust != "marginal" {
+            found.insert(sig.public_key().fingerprint(HashAlg::Sha256).to_string());
+        }
+
+        report.signatures.push(Entry::good(sig, signer, "good signature from"));
+    }
+
+    let required = threshold.unwrap_or(sigs.len());
+    report.threshold = Some(Threshold { found: found.len(), required });
+
+    if found.len() < required {
+        return Err(Error::Threshold(found.len(), required).into());
+    }
+
+    countersignatures::<S>(&sig, verify, report)
+}
+
+fn countersignatures<S: System>(path: &Path, verify: impl Fn(&SshSig, &str, &[u8], Option<&str>) -> Result<Signer>, report: &mut Report) -> Result<()> {
+    let mut path = path.to_owned();
+    loop {
+        let next = append(&path, ".csig");
+        let data = match S::read(&next.clone().into()) {
+            Ok(data)                                  => data,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e)                                    => Err(e).context(&next)?,
+        };
+
+        let msg  = countersigned(&read::<S>(&path.into())?)?;
+        let sigs = bundle(&data).map_err(|e| anyhow!("{}: {e}", next.display()))?;
+
+        let mut level = Vec::new();
+        for sig in sigs {
+            let signer = verify(&sig, COUNTERSIGN, &msg, None).map_err(|e| {
+                anyhow!("{}: {e}", next.display())
+            });
+            let signer = match signer {
+                Ok(signer) => signer,
+                Err(error) => {
+                    report.countersignatures.push(level);
+                    return Err(error);
+                }
+            };
+            let passed = signer.passed();
+            level.push(Entry::good(&sig, signer, "countersigned by"));
+            if !passed {
+                report.countersignatures.push(level);
+                return Err(Error::Policy.into());
+            }
+        }
+        report.countersignatures.push(level);
+
+        path = next;
+    }
+}
+
+fn signer<S: System>(signet: &Signet<S>, sig: &SshSig, ns: &str, msg: &[u8], policy: Option<&Policy>, revoked: Option<&Revocations>, path: Option<&str>) -> Result<Signer> {
     let fp  = sig.public_key().fingerprint(HashAlg::Sha256);
     let key = hex::encode(fp);
-    let key = signet.find(&key)?;
 
-    key.public_key().verify(&ns, &msg, &sig)?;
+    let (contact, succeeds) = trusted(signet, sig.public_key(), revoked)?;
+
+    let public = check(sig, ns, msg)?;
+
+    let trust = match contact.as_ref().map(|contact| contact.trust) {
+        Some(Trust::Never)    => return Err(Error::Untrusted.into()),
+        Some(Trust::Marginal) => "marginal",
+        Some(Trust::Full)     => "full",
+        None                  => "own",
+    };
+
+    let principals = match &contact {
+        Some(contact) => contact.principals.clone(),
+        None          => signet.current()?.principals(&key).map(|allowed| {
+            allowed.principals.clone()
+        }).unwrap_or_default(),
+    };
+
+    let rules = match policy {
+        Some(policy) => policy.evaluate(&Signature {
+            key:        &public,
+            principals: &principals,
+            namespace:  ns,
+            path:       path,
+            sk:         sig.sk(),
+        })?,
+        None         => Vec::new(),
+    }.into_iter().map(|(rule, verdict)| Rule {
+        rule:   rule.to_owned(),
+        passed: verdict.passed(),
+        reason: verdict.reason().to_owned(),
+    }).collect();
+
+    Ok(Signer { principals, trust, succeeds, rules })
+}
+
+fn trusted<S: System>(signet: &Signet<S>, key: &KeyData, revoked: Option<&Revocations>) -> Result<(Option<Contact>, Vec<String>)> {
+    let keychain = signet.current()?;
+    let contacts = signet.contacts()?;
+
+    let successions = keychain.successions().chain(contacts.successions()).collect::<Vec<_>>();
+
+    let chain = Chain { keychain: &keychain, contacts: &contacts, successions: &successions, revoked };
+    chain.follow(&PublicKey::from(key.clone()), &mut Vec::new())
+}
+
+struct Chain<'a> {
+    keychain:    &'a Keychain,
+    contacts:    &'a Contacts,
+    successions: &'a [&'a Succession],
+    revoked:     Option<&'a Revocations>,
+}
+
+impl Chain<'_> {
+    fn follow(&self, key: &PublicKey, succeeds: &mut Vec<String>) -> Result<(Option<Contact>, Vec<String>)> {
+        let Self { keychain, contacts, successions, revoked } = self;
+
+        let id = hex::encode(key.fingerprint(HashAlg::Sha256));
+
+        if keychain.revocation(&id).is_some() {
+            return Err(Error::Revoked.into());
+        }
+
+        if revoked.is_some_and(|list| list.contains(key.key_data())) {
+            return Err(Error::Revoked.into());
+        }
+
+        if keychain.key(&id).is_some() {
+            return Ok((None, succeeds.clone()));
+        }
+
+        if let Ok(contact) = contacts.find(&id) {
+            return Ok((Some(contact.clone()), succeeds.clone()));
+        }
+
+        let mut error = anyhow::Error::from(Error::Untrusted);
+        for succession in successions.iter().filter(|succession| succession.succeeds(key)) {
+            let fp = succession.old.fingerprint(HashAlg::Sha256).to_string();
+            if succeeds.contains(&fp) {
+                continue;
+            }
+
+            if let Err(e) = succession.verify() {
+                error = e.into();
+                continue;
+            }
+
+            succeeds.push(fp);
+            match self.follow(&succession.old, succeeds) {
+                Ok(found) => return Ok(found),
+                Err(e)    => error = e,
+            }
+            succeeds.pop();
+        }
+
+        Err(error)
+    }
+}
+
+pub fn countersigned(data: &[u8]) -> Result<Vec<u8>> {
+    let mut msg = Vec::new();
+    for sig in bundle(data)? {
+        sig.encode(&mut msg)?;
+    }
+    Ok(msg)
+}
+
+pub fn bundle(data: &[u8]) -> Result<Vec<SshSig>> {
+    let data = std::str::from_utf8(data)?;
+
+    let mut sigs  = Vec::new();
+    let mut block = String::new();
+    for line in data.lines().filter(|line| !line.trim().is_empty()) {
+        block.push_str(line);
+        block.push('\n');
+        if line.trim() == END {
+            sigs.push(SshSig::from_pem(&block)?);
+            block.clear();
+        }
+    }
+
+    match (sigs.is_empty(), block.is_empty()) {
+        (false, true) => Ok(sigs),
+        _             => Err(anyhow!("invalid signature file")),
+    }
+}
+
+fn valid<S: System>(signet: &Signet<S>, key: &KeyData, time: OffsetDateTime) -> Result<()> {
+    let id = hex::encode(key.fingerprint(HashAlg::Sha256));
+    match signet.current()?.validity(&id) {
+        Some(validity) => Ok(validity.check(time)?),
+        None           => Ok(()),
+    }
+}
+
+pub fn check(sig: &SshSig, ns: &str, msg: &[u8]) -> Result<PublicKey, Error> {
+    match sig.verify(ns, msg) {
+        Ok(())                         => Ok(PublicKey::from(sig.public_key().clone())),
+        Err(ssh_key::Error::Namespace) => Err(Error::Namespace),
+        Err(_)                         => Err(Error::Signature),
+    }
+}
+
+fn relative(path: &Path) -> Result<String> {
+    let cwd  = env::current_dir()?;
+    let path = path.strip_prefix(&cwd).unwrap_or(path);
+    let path = path.components().filter(|c| *c != Component::CurDir).map(|c| {
+        c.as_os_str().to_string_lossy().into_owned()
+    }).collect::<Vec<_>>();
+    Ok(path.join("/"))
+}
+
+pub fn unrevoked<S: System>(path: &Path, key: &KeyData) -> Result<()> {
+    match revocations::<S>(path)?.contains(key) {
+        true  => Err(Error::Revoked.into()),
+        false => Ok(()),
+    }
+}
+
+fn revocations<S: System>(path: &Path) -> Result<Revocations> {
+    let data = read::<S>(&path.to_owned().into())?;
+    Revocations::parse(&data).map_err(|error| {
+        anyhow!("{}: {error}", path.display())
+    })
+}
+
+impl Entry {
+    fn good(sig: &SshSig, signer: Signer, action: &'static str) -> Self {
+        Self {
+            key:          public(sig),
+            status:       "good",
+            security_key: security_key(sig),
+            signer:       Some(signer),
+            error:        None,
+            action:       action,
+        }
+    }
+
+    fn rejected(sig: &SshSig, error: String) -> Self {
+        Self {
+            key:          public(sig),
+            status:       "rejected",
+            security_key: security_key(sig),
+            signer:       None,
+            error:        Some(error),
+            action:       "rejected signature from",
+        }
+    }
+}
+
+impl Signer {
+    fn passed(&self) -> bool {
+        self.rules.iter().all(|rule| rule.passed)
+    }
+}
+
+fn public(sig: &SshSig) -> Public {
+    Public::from(&PublicKey::from(sig.public_key().clone()))
+}
+
+fn security_key(sig: &SshSig) -> Option<SecurityKey> {
+    sig.sk().map(|sk| SecurityKey {
+        flags:        sk.flags,
+        counter:      sk.counter,
+        presence:     sk.presence(),
+        verification: sk.verification(),
+    })
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut lines = self.signatures.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        if let Some(Threshold { found, required }) = self.threshold {
+            if found >= required {
+                lines.push(format!("threshold met, {found}/{required} required signatures"));
+            }
+        }
+
+        lines.extend(self.countersignatures.iter().flatten().map(ToString::to_string));
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let action = self.action;
+        let fp     = &self.key.fingerprint;
+
+        let Some(signer) = &self.signer else {
+            return write!(f, "{action} {fp}: {}", self.error.as_deref().unwrap_or_default());
+        };
+
+        match signer.principals.is_empty() || signer.trust == "own" {
+            true  => write!(f, "{action} {fp}")?,
+            false => write!(f, "{action} {} {fp}", signer.principals.join(","))?,
+        }
+
+        if let Some(sk) = &self.security_key {
+            write!(f, " ({sk})")?;
+        }
+
+        if let Some(old) = signer.succeeds.last() {
+            write!(f, " (succeeds {old})")?;
+        }
+
+        if signer.trust == "marginal" {
+            write!(f, " (marginal trust)")?;
+        }
+
+        for rule in &signer.rules {
+            let status = if rule.passed { "passed" } else { "failed" };
+            write!(f, "\nrule {}: {status}, {}", rule.rule, rule.reason)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for SecurityKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let presence = match self.presence {
+            true  => "user present",
+            false => "no user presence",
+        };
+        write!(f, "security key: {presence}")?;
+        if self.verification {
+            write!(f, ", user verified")?;
+        }
+        write!(f, ", counter {}", self.counter)
+    }
+}
 
-    println!("good signature from {fp}");
+impl std::error::Error for Error {}
 
-    Ok(())
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Namespace => write!(f, "namespace does not match"),
+            Self::Signature => write!(f, "incorrect signature"),
+            Self::Revoked   => write!(f, "key is revoked"),
+            Self::Untrusted => write!(f, "key is not trusted"),
+            Self::Policy    => write!(f, "signature does not satisfy policy"),
+            Self::Threshold(found, required) => {
+                write!(f, "only {found} of {required} required signatures")
+            },
+        }
+    }
 }
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::str::FromStr;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use ssh_key::{HashAlg, PublicKey};
+use crate::Succession;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Contacts {
+    version:  u64,
+    contacts: IndexMap<String, Contact>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    sources:  IndexMap<String, Source>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rotated:  Vec<Succession>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Contact {
+    pub key:        PublicKey,
+    pub principals: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note:       String,
+    pub trust:      Trust,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source:     Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Source {
+    pub principals: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note:       String,
+    pub trust:      Trust,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum Trust {
+    Full,
+    Marginal,
+    Never,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    ContactNotFound,
+    ContactAmbiguous,
+    InvalidTrust(String),
+}
+
+impl Contacts {
+    pub fn add(&mut self, contact: Contact) -> String {
+        let id = contact.id();
+        self.contacts.insert(id.clone(), contact);
+        id
+    }
+
+    pub fn delete(&mut self, id: &str) -> Option<Contact> {
+        self.contacts.shift_remove(id)
+    }
+
+    pub fn find(&self, prefix: &str) -> Result<&Contact, Error> {
+        let contacts = self.contacts.iter().filter_map(|(id, contact)| {
+            id.starts_with(prefix).then_some(contact)
+        }).collect::<Vec<_>>();
+
+        match contacts[..] {
+            [contact] => Ok(contact),
+            []        => Err(Error::ContactNotFound),
+            [..]      => Err(Error::ContactAmbiguous),
+        }
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.contacts.contains_key(id)
+    }
+
+    pub fn contacts(&self) -> impl Iterator<Item = &Contact> {
+        self.contacts.values()
+    }
+
+    pub fn import(&mut self, location: &str, source: Source) {
+        self.sources.insert(location.to_owned(), source);
+    }
+
+    pub fn imported(&self, location: &str) -> Vec<String> {
+        self.contacts.iter().filter_map(|(id, contact)| {
+            let source = contact.source.as_deref();
+            (source == Some(location)).then(|| id.clone())
+        }).collect()
+    }
+
+    pub fn sources(&self) -> impl Iterator<Item = (&String, &Source)> {
+        self.sources.iter()
+    }
+
+    pub fn rotate(&mut self, succession: Succession) {
+        self.rotated.retain(|s| s.old != succession.old || s.new != succession.new);
+        self.rotated.push(succession);
+    }
+
+    pub fn successions(&self) -> impl Iterator<Item = &Succession> {
+        self.rotated.iter()
+    }
+}
+
+impl Default for Contacts {
+    fn default() -> Self {
+        Self {
+            version:  1,
+            contacts: IndexMap::new(),
+            sources:  IndexMap::new(),
+            rotated:  Vec::new(),
+        }
+    }
+}
+
+impl Contact {
+    pub fn id(&self) -> String {
+        let algorithm   = HashAlg::Sha256;
+        let fingerprint = self.key.fingerprint(algorithm);
+        hex::encode(fingerprint)
+    }
+}
+
+impl Source {
+    pub fn contact(&self, key: PublicKey, location: Option<&str>) -> Contact {
+        let principals = match self.principals.is_empty() {
+            true  => vec![key.comment().to_owned()],
+            false => self.principals.clone(),
+        }.into_iter().filter(|principal| !principal.is_empty()).collect();
+
+        Contact {
+            key:        key,
+            principals: principals,
+            note:       self.note.clone(),
+            trust:      self.trust,
+            source:     location.map(str::to_owned),
+        }
+    }
+}
+
+impl FromStr for Trust {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str {
+            "full"     => Ok(Self::Full),
+            "marginal" => Ok(Self::Marginal),
+            "never"    => Ok(Self::Never),
+            other      => Err(Error::InvalidTrust(other.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Trust {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Full     => write!(f, "full"),
+            Self::Marginal => write!(f, "marginal"),
+            Self::Never    => write!(f, "never"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ContactNotFound     => write!(f, "contact not found"),
+            Self::ContactAmbiguous    => write!(f, "contact ambiguous"),
+            Self::InvalidTrust(trust) => write!(f, "invalid trust level '{trust}'"),
+        }
+    }
+}
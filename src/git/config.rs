@@ -0,0 +1,390 @@
+use std::{env, fmt};
+use std::path::{Path, PathBuf};
+use dirs::home_dir;
+use crate::System;
+
+#[derive(Debug, Default)]
+pub struct Config {
+    lines: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Line<'a> {
+    Section(&'a str, Option<String>),
+    Entry(&'a str, &'a str),
+    Other,
+}
+
+#[derive(Debug)]
+struct Name<'a> {
+    section:    &'a str,
+    subsection: Option<&'a str>,
+    key:        &'a str,
+}
+
+impl Config {
+    pub fn parse(data: &[u8]) -> Self {
+        let data = String::from_utf8_lossy(data);
+        let mut lines = Vec::<String>::new();
+        let mut joined = false;
+
+        for line in data.split_inclusive('\n') {
+            match lines.last_mut() {
+                Some(last) if joined => last.push_str(line),
+                _                    => lines.push(line.to_owned()),
+            }
+            joined = continues(line);
+        }
+
+        Self { lines }
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        let name = Name::parse(name)?;
+        let mut found   = None;
+        let mut current = false;
+
+        for line in &self.lines {
+            match parse(line) {
+                Line::Section(section, sub) => current = name.section(section, sub),
+                Line::Entry(key, value)     => if current && name.key(key) {
+                    found = Some(value);
+                },
+                Line::Other                 => (),
+            }
+        }
+
+        found.map(unquote)
+    }
+
+    pub fn path(&self, name: &str) -> Option<PathBuf> {
+        let path = self.get(name)?;
+        match path.strip_prefix("~/") {
+            Some(rest) => Some(home_dir()?.join(rest)),
+            None       => Some(path.into()),
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        let Some(name) = Name::parse(name) else { return };
+        let entry = format!("\t{} = {}\n", name.key, quote(value));
+
+        let mut section = None;
+        let mut last    = None;
+        let mut current = false;
+
+        for (index, line) in self.lines.iter().enumerate() {
+            match parse(line) {
+                Line::Section(s, sub) => {
+                    current = name.section(s, sub);
+                    if current {
+                        section = Some(index);
+                    }
+                },
+                Line::Entry(key, _)   => if current && name.key(key) {
+                    last = Some(index);
+                },
+                Line::Other           => (),
+            }
+        }
+
+        if let Some(index) = last {
+            self.lines[index] = entry;
+        } else if let Some(index) = section {
+            let end = self.lines[index + 1..].iter().position(|line| {
+                matches!(parse(line), Line::Section(..))
+            }).map_or(self.lines.len(), |n| index + 1 + n);
+            let end = (index + 1..end).rev().find(|n| {
+                !self.lines[*n].trim().is_empty()
+            }).map_or(index + 1, |n| n + 1);
+            self.lines.insert(end, entry);
+        } else {
+            if let Some(last) = self.lines.last_mut().filter(|line| !line.ends_with('\n')) {
+                last.push('\n');
+            }
+            self.lines.push(name.header());
+            self.lines.push(entry);
+        }
+    }
+
+    pub fn unset(&mut self, name: &str) -> bool {
+        let Some(name) = Name::parse(name) else { return false };
+        let mut current = false;
+        let mut removed = false;
+
+        self.lines.retain(|line| {
+            match parse(line) {
+                Line::Section(s, sub) => current = name.section(s, sub),
+                Line::Entry(key, _)   => if current && name.key(key) {
+                    removed = true;
+                    return false;
+                },
+                Line::Other           => (),
+            }
+            true
+        });
+
+        if removed {
+            self.prune();
+        }
+
+        removed
+    }
+
+    fn prune(&mut self) {
+        let mut index = 0;
+        while index < self.lines.len() {
+            let end = self.lines[index + 1..].iter().position(|line| {
+                !line.trim().is_empty()
+            }).map_or(self.lines.len(), |n| index + 1 + n);
+
+            let empty = matches!(parse(&self.lines[index]), Line::Section(..));
+            let next  = self.lines.get(end).map(|line| parse(line));
+            let next  = next.is_none_or(|line| matches!(line, Line::Section(..)));
+
+            if empty && next {
+                self.lines.drain(index..end);
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+pub fn global<S: System>() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("GIT_CONFIG_GLOBAL") {
+        return Some(path.into());
+    }
+
+    let home = home_dir()?;
+    let xdg  = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _                            => home.join(".config"),
+    }.join("git").join("config");
+    let home = home.join(".gitconfig");
+
+    let exists = |path: &Path| S::read(&path.to_owned().into()).is_ok();
+    match !exists(&home) && exists(&xdg) {
+        true  => Some(xdg),
+        false => Some(home),
+    }
+}
+
+impl Name<'_> {
+    fn parse(name: &str) -> Option<Name<'_>> {
+        let (section, rest) = name.split_once('.')?;
+        let (subsection, key) = match rest.rsplit_once('.') {
+            Some((sub, key)) => (Some(sub), key),
+            None             => (None, rest),
+        };
+        Some(Name { section, subsection, key })
+    }
+
+    fn section(&self, section: &str, subsection: Option<String>) -> bool {
+        self.section.eq_ignore_ascii_case(section) && self.subsection == subsection.as_deref()
+    }
+
+    fn key(&self, key: &str) -> bool {
+        self.key.eq_ignore_ascii_case(key)
+    }
+
+    fn header(&self) -> String {
+        match self.subsection {
+            Some(sub) => format!("[{} \"{}\"]\n", self.section, escape(sub)),
+            None      => format!("[{}]\n", self.section),
+        }
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.lines.iter().try_for_each(|line| f.write_str(line))
+    }
+}
+
+fn parse(line: &str) -> Line<'_> {
+    let line = line.trim_start();
+
+    if let Some(header) = line.strip_prefix('[') {
+        let Some(end) = header.find(']') else { return Line::Other };
+        let header = &header[..end];
+        return match header.split_once([' ', '\t']) {
+            Some((section, sub)) => {
+                let sub = sub.trim().strip_prefix('"').and_then(|s| s.strip_suffix('"'));
+                Line::Section(section, sub.map(unescape))
+            },
+            None                 => match header.split_once('.') {
+                Some((section, sub)) => Line::Section(section, Some(sub.to_ascii_lowercase())),
+                None                 => Line::Section(header, None),
+            },
+        };
+    }
+
+    let end = line.find(|c: char| !c.is_ascii_alphanumeric() && c != '-').unwrap_or(line.len());
+    let (key, rest) = line.split_at(end);
+    if key.is_empty() || !key.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Line::Other;
+    }
+
+    let rest = rest.trim_start_matches([' ', '\t']);
+    match rest.strip_prefix('=') {
+        Some(value) => Line::Entry(key, value),
+        None        => Line::Entry(key, "true"),
+    }
+}
+
+fn continues(line: &str) -> bool {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let tail = line.len() - line.trim_end_matches('\\').len();
+    tail % 2 == 1
+}
+
+fn unquote(value: &str) -> String {
+    let mut out    = String::new();
+    let mut quoted = false;
+    let mut trim   = 0;
+    let mut chars  = value.trim_start().chars();
+
+    while let Some(char) = chars.next() {
+        let (char, space) = match char {
+            '"'                  => {
+                quoted = !quoted;
+                trim   = 0;
+                continue;
+            },
+            '#' | ';' if !quoted => break,
+            '\r' | '\n'          => continue,
+            '\\'                 => match chars.next() {
+                Some('n')  => ('\n', false),
+                Some('t')  => ('\t', false),
+                Some('b')  => {
+                    out.pop();
+                    continue;
+                },
+                Some('\n') => continue,
+                Some(char) => (char, false),
+                None       => break,
+            },
+            char                 => (char, !quoted && matches!(char, ' ' | '\t')),
+        };
+        out.push(char);
+        trim = if space { trim + 1 } else { 0 };
+    }
+
+    out.truncate(out.len() - trim);
+    out
+}
+
+fn unescape(sub: &str) -> String {
+    let mut out   = String::new();
+    let mut chars = sub.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => out.extend(chars.next()),
+            char => out.push(char),
+        }
+    }
+    out
+}
+
+fn escape(sub: &str) -> String {
+    sub.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn quote(value: &str) -> String {
+    let special = value.starts_with([' ', '\t']) || value.ends_with([' ', '\t']) || value.contains(['#', ';']);
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\t', "\\t");
+    match special {
+        true  => format!("\"{escaped}\""),
+        false => escaped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+    use super::Config;
+
+    const CONFIG: &str = "\
+# user settings
+[user]
+\tname = Alice Example ; comment
+\temail = \"alice@example.com\"
+[gpg \"ssh\"]
+\tallowedSignersFile = ~/.config/git/allowed_signers
+[Commit]
+\tGpgSign
+[remote \"Origin\"]
+\turl = https://example.com/\\
+repo.git
+[core.Editor]
+\tcommand = \"vim  \"  # trailing
+";
+
+    fn git(path: &str, name: &str) -> String {
+        let output = Command::new("git").args(["config", "-f", path, "--get", name]).output().unwrap();
+        assert!(output.status.success(), "{name}");
+        String::from_utf8(output.stdout).unwrap().trim_end_matches('\n').to_owned()
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(Config::parse(CONFIG.as_bytes()).to_string(), CONFIG);
+    }
+
+    #[test]
+    fn get() {
+        let config = Config::parse(CONFIG.as_bytes());
+        assert_eq!(config.get("user.name").as_deref(), Some("Alice Example"));
+        assert_eq!(config.get("user.email").as_deref(), Some("alice@example.com"));
+        assert_eq!(config.get("USER.NAME").as_deref(), Some("Alice Example"));
+        assert_eq!(config.get("gpg.ssh.allowedsignersfile").as_deref(), Some("~/.config/git/allowed_signers"));
+        assert_eq!(config.get("commit.gpgsign").as_deref(), Some("true"));
+        assert_eq!(config.get("remote.Origin.url").as_deref(), Some("https://example.com/repo.git"));
+        assert_eq!(config.get("remote.origin.url"), None);
+        assert_eq!(config.get("core.editor.command").as_deref(), Some("vim  "));
+        assert_eq!(config.get("user.signingkey"), None);
+    }
+
+    #[test]
+    fn set_and_unset() {
+        let tmp  = TempDir::new().unwrap();
+        let path = tmp.path().join("config");
+        let file = path.to_str().unwrap();
+
+        let mut config = Config::parse(CONFIG.as_bytes());
+        config.set("user.name", "Bob");
+        config.set("user.signingkey", "key::ssh-ed25519 AAAA # key");
+        config.set("gpg.format", "ssh");
+        config.set("gpg.ssh.program", " signet ");
+        config.set("tag.gpgSign", "true");
+        fs::write(&path, config.to_string()).unwrap();
+
+        assert_eq!(git(file, "user.name"), "Bob");
+        assert_eq!(git(file, "user.email"), "alice@example.com");
+        assert_eq!(git(file, "user.signingkey"), "key::ssh-ed25519 AAAA # key");
+        assert_eq!(git(file, "gpg.format"), "ssh");
+        assert_eq!(git(file, "gpg.ssh.program"), " signet ");
+        assert_eq!(git(file, "tag.gpgsign"), "true");
+        assert_eq!(git(file, "remote.Origin.url"), "https://example.com/repo.git");
+
+        let parsed = Config::parse(config.to_string().as_bytes());
+        assert_eq!(parsed.get("gpg.ssh.program").as_deref(), Some(" signet "));
+        assert_eq!(parsed.to_string(), config.to_string());
+
+        assert!(config.unset("tag.gpgsign"));
+        assert!(config.unset("gpg.format"));
+        assert!(!config.unset("gpg.format"));
+        assert!(!config.to_string().contains("[tag]"));
+        assert!(!config.to_string().contains("[gpg]"));
+        assert_eq!(config.get("gpg.ssh.program").as_deref(), Some(" signet "));
+
+        config.unset("user.name");
+        config.unset("user.signingkey");
+        config.unset("user.email");
+        assert!(!config.to_string().contains("[user]"));
+        assert_eq!(config.get("commit.gpgsign").as_deref(), Some("true"));
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use super::Oid;
+
+#[derive(Debug)]
+pub enum Error {
+    Revision(String),
+    Missing(Oid),
+    Corrupt(Oid),
+    Pack(PathBuf),
+    Kind(Oid, &'static str),
+    System(PathBuf, io::Error),
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Revision(rev)     => write!(f, "unknown revision '{rev}'"),
+            Self::Missing(oid)      => write!(f, "object {oid} not found"),
+            Self::Corrupt(oid)      => write!(f, "object {oid} is corrupt"),
+            Self::Pack(path)        => write!(f, "{}: invalid pack", path.display()),
+            Self::Kind(oid, kind)   => write!(f, "object {oid} is not a {kind}"),
+            Self::System(path, err) => write!(f, "{}: {err}", path.display()),
+        }
+    }
+}
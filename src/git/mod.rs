@@ -0,0 +1,16 @@
+pub use config::Config;
+pub use config::global;
+
+pub use error::Error;
+
+pub use object::{Commit, Kind, Object, Signed, Tag};
+pub use oid::Oid;
+
+pub use repository::Repository;
+
+mod config;
+mod error;
+mod object;
+mod oid;
+mod pack;
+mod repository;
@@ -0,0 +1,178 @@
+use super::{Error, Oid};
+
+const HEADERS: &[&[u8]] = &[b"gpgsig", b"gpgsig-sha256"];
+
+const MARKERS: &[&[u8]] = &[
+    b"-----BEGIN PGP SIGNATURE-----",
+    b"-----BEGIN PGP MESSAGE-----",
+    b"-----BEGIN SIGNED MESSAGE-----",
+    b"-----BEGIN SSH SIGNATURE-----",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Commit,
+    Tree,
+    Blob,
+    Tag,
+}
+
+#[derive(Clone, Debug)]
+pub struct Object {
+    pub kind: Kind,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Commit {
+    pub parents:   Vec<Oid>,
+    pub time:      i64,
+    pub signature: Option<Signed>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Tag {
+    pub target:    Oid,
+    pub time:      i64,
+    pub signature: Option<Signed>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Signed {
+    pub payload:   Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl Kind {
+    pub fn parse(name: &[u8]) -> Option<Self> {
+        Some(match name {
+            b"commit" => Self::Commit,
+            b"tree"   => Self::Tree,
+            b"blob"   => Self::Blob,
+            b"tag"    => Self::Tag,
+            _         => return None,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Commit => "commit",
+            Self::Tree   => "tree",
+            Self::Blob   => "blob",
+            Self::Tag    => "tag",
+        }
+    }
+}
+
+impl Commit {
+    pub fn parse(oid: Oid, object: &Object) -> Result<Self, Error> {
+        if object.kind != Kind::Commit {
+            return Err(Error::Kind(oid, Kind::Commit.name()));
+        }
+
+        let data = &object.data[..];
+        let body = body(data).ok_or(Error::Corrupt(oid))?;
+
+        let mut commit = Self {
+            parents:   Vec::new(),
+            time:      0,
+            signature: None,
+        };
+
+        let mut payload   = Vec::new();
+        let mut signature = None::<Vec<u8>>;
+        let mut header    = &b""[..];
+
+        for line in data[..body].split_inclusive(|b| *b == b'\n') {
+            if let Some(rest) = line.strip_prefix(b" ").filter(|_| HEADERS.contains(&header)) {
+                if let Some(sig) = signature.as_mut().filter(|_| header == b"gpgsig") {
+                    sig.extend_from_slice(rest);
+                }
+                continue;
+            }
+
+            let (name, value) = split(line, b' ');
+            header = name;
+
+            match name {
+                b"parent"    => commit.parents.push(oid_from(value).ok_or(Error::Corrupt(oid))?),
+                b"committer" => commit.time = time(value).ok_or(Error::Corrupt(oid))?,
+                b"gpgsig"    => signature = Some(value.to_vec()),
+                _            => (),
+            }
+
+            if !HEADERS.contains(&name) {
+                payload.extend_from_slice(line);
+            }
+        }
+
+        payload.extend_from_slice(&data[body..]);
+
+        commit.signature = signature.map(|signature| Signed { payload, signature });
+
+        Ok(commit)
+    }
+}
+
+impl Tag {
+    pub fn parse(oid: Oid, object: &Object) -> Result<Self, Error> {
+        if object.kind != Kind::Tag {
+            return Err(Error::Kind(oid, Kind::Tag.name()));
+        }
+
+        let data = &object.data[..];
+        let body = body(data).ok_or(Error::Corrupt(oid))?;
+
+        let mut target = None;
+        let mut time   = 0;
+
+        for line in data[..body].split_inclusive(|b| *b == b'\n') {
+            let (field, value) = split(line, b' ');
+            let value = value.strip_suffix(b"\n").unwrap_or(value);
+            match field {
+                b"object" => target = oid_from(value),
+                b"tagger" => time   = self::time(value).unwrap_or(0),
+                _         => (),
+            }
+        }
+
+        let signature = data[body..].split_inclusive(|b| *b == b'\n').scan(body, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        }).filter(|(_, line)| {
+            MARKERS.iter().any(|marker| line.starts_with(marker))
+        }).last().map(|(start, _)| Signed {
+            payload:   data[..start].to_vec(),
+            signature: data[start..].to_vec(),
+        });
+
+        Ok(Self {
+            target:    target.ok_or(Error::Corrupt(oid))?,
+            time:      time,
+            signature: signature,
+        })
+    }
+}
+
+fn body(data: &[u8]) -> Option<usize> {
+    data.windows(2).position(|pair| pair == b"\n\n").map(|n| n + 1)
+}
+
+fn split(line: &[u8], sep: u8) -> (&[u8], &[u8]) {
+    match line.iter().position(|b| *b == sep) {
+        Some(n) => (&line[..n], &line[n + 1..]),
+        None    => (line.strip_suffix(b"\n").unwrap_or(line), &[]),
+    }
+}
+
+fn oid_from(hex: &[u8]) -> Option<Oid> {
+    let hex = std::str::from_utf8(hex).ok()?;
+    hex.trim_end().parse().ok()
+}
+
+fn time(ident: &[u8]) -> Option<i64> {
+    let ident = std::str::from_utf8(ident).ok()?;
+    let (_, date) = ident.rsplit_once('>')?;
+    date.split_whitespace().next()?.parse().ok()
+}
@@ -0,0 +1,37 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Oid([u8; 20]);
+
+impl Oid {
+    pub const LEN: usize = 20;
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self(bytes.try_into().ok()?))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn short(&self) -> String {
+        hex::encode(&self.0[..6])
+    }
+}
+
+impl FromStr for Oid {
+    type Err = hex::FromHexError;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let mut oid = [0u8; Self::LEN];
+        hex::decode_to_slice(str, &mut oid)?;
+        Ok(Self(oid))
+    }
+}
+
+impl fmt::Display for Oid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
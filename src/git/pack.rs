@@ -0,0 +1,248 @@
+use std::cell::OnceCell;
+use std::cmp::Ordering;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use flate2::read::ZlibDecoder;
+use crate::System;
+use super::{Error, Kind, Oid};
+
+const MAGIC:   &[u8; 4] = b"\xfftOc";
+const VERSION: u32      = 2;
+const FANOUT:  usize    = 8;
+const NAMES:   usize    = FANOUT + 256 * 4;
+
+const OBJ_OFS_DELTA: u8 = 6;
+const OBJ_REF_DELTA: u8 = 7;
+
+#[derive(Debug)]
+pub struct Pack {
+    path:  PathBuf,
+    index: Vec<u8>,
+    count: usize,
+    data:  OnceCell<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub enum Entry {
+    Base(Kind),
+    Offset(u64),
+    Ref(Oid),
+}
+
+impl Pack {
+    pub fn open<S: System>(idx: &Path) -> Result<Self, Error> {
+        let path  = idx.with_extension("pack");
+        let index = S::read(&idx.to_owned().into()).map_err(|e| {
+            Error::System(idx.to_owned(), e)
+        })?;
+
+        let invalid = || Error::Pack(idx.to_owned());
+
+        if index.len() < NAMES || &index[..4] != MAGIC || u32_at(&index, 4) != Some(VERSION) {
+            return Err(invalid());
+        }
+
+        let count = u32_at(&index, NAMES - 4).ok_or_else(invalid)? as usize;
+        if index.len() < NAMES + count * (Oid::LEN + 8) {
+            return Err(invalid());
+        }
+
+        let data = OnceCell::new();
+
+        Ok(Self { path, index, count, data })
+    }
+
+    pub fn find(&self, oid: &Oid) -> Option<u64> {
+        let first  = oid.as_bytes()[0] as usize;
+        let mut lo = match first {
+            0 => 0,
+            n => self.fanout(n - 1)?,
+        };
+        let mut hi = self.fanout(first)?;
+
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match self.name(mid)?.cmp(oid.as_bytes()) {
+                Ordering::Less    => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal   => return self.offset(mid),
+            }
+        }
+
+        None
+    }
+
+    pub fn prefixed<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = Oid> + 'a {
+        (0..self.count).filter_map(|n| Oid::from_bytes(self.name(n)?)).filter(move |oid| {
+            oid.to_string().starts_with(prefix)
+        })
+    }
+
+    pub fn entry<S: System>(&self, offset: u64) -> Result<(Entry, Vec<u8>), Error> {
+        let data    = self.data::<S>()?;
+        let invalid = || Error::Pack(self.path.clone());
+
+        let mut pos  = usize::try_from(offset).map_err(|_| invalid())?;
+        let mut byte = *data.get(pos).ok_or_else(invalid)?;
+        let kind     = (byte >> 4) & 7;
+        pos += 1;
+
+        while byte & 0x80 != 0 {
+            byte = *data.get(pos).ok_or_else(invalid)?;
+            pos += 1;
+        }
+
+        let entry = match kind {
+            OBJ_OFS_DELTA => {
+                let mut byte = *data.get(pos).ok_or_else(invalid)?;
+                let mut base = (byte & 0x7f) as u64;
+                pos += 1;
+                while byte & 0x80 != 0 {
+                    byte = *data.get(pos).ok_or_else(invalid)?;
+                    base = ((base + 1) << 7) | (byte & 0x7f) as u64;
+                    pos += 1;
+                }
+                let base = offset.checked_sub(base).filter(|base| *base > 0 && *base < offset);
+                Entry::Offset(base.ok_or_else(invalid)?)
+            },
+            OBJ_REF_DELTA => {
+                let oid = data.get(pos..pos + Oid::LEN).and_then(Oid::from_bytes);
+                pos += Oid::LEN;
+                Entry::Ref(oid.ok_or_else(invalid)?)
+            },
+            kind          => Entry::Base(match kind {
+                1 => Kind::Commit,
+                2 => Kind::Tree,
+                3 => Kind::Blob,
+                4 => Kind::Tag,
+                _ => return Err(invalid()),
+            }),
+        };
+
+        let body = data.get(pos..).ok_or_else(invalid)?;
+        let body = inflate(body).ok_or_else(invalid)?;
+
+        Ok((entry, body))
+    }
+
+    fn data<S: System>(&self) -> Result<&[u8], Error> {
+        if let Some(data) = self.data.get() {
+            return Ok(data);
+        }
+        let data = S::read(&self.path.clone().into()).map_err(|e| {
+            Error::System(self.path.clone(), e)
+        })?;
+        Ok(self.data.get_or_init(|| data))
+    }
+
+    fn fanout(&self, n: usize) -> Option<usize> {
+        u32_at(&self.index, FANOUT + n * 4).map(|n| n as usize)
+    }
+
+    fn name(&self, n: usize) -> Option<&[u8]> {
+        let start = NAMES + n * Oid::LEN;
+        self.index.get(start..start + Oid::LEN)
+    }
+
+    fn offset(&self, n: usize) -> Option<u64> {
+        let offsets = NAMES + self.count * (Oid::LEN + 4);
+        let offset  = u32_at(&self.index, offsets + n * 4)?;
+        if offset & 0x8000_0000 == 0 {
+            return Some(offset as u64);
+        }
+        let large = offsets + self.count * 4 + (offset & 0x7fff_ffff) as usize * 8;
+        let bytes = self.index.get(large..large + 8)?;
+        Some(u64::from_be_bytes(bytes.try_into().ok()?))
+    }
+}
+
+pub fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    ZlibDecoder::new(data).read_to_end(&mut out).ok()?;
+    Some(out)
+}
+
+pub fn apply(base: &[u8], delta: &[u8]) -> Option<Vec<u8>> {
+    let mut delta = delta.iter().copied();
+
+    let size = varint(&mut delta)?;
+    if size != base.len() {
+        return None;
+    }
+
+    let size    = varint(&mut delta)?;
+    let mut out = Vec::new();
+
+    while let Some(cmd) = delta.next() {
+        if cmd & 0x80 != 0 {
+            let mut offset = 0usize;
+            let mut length = 0usize;
+            for bit in 0..4 {
+                if cmd & (1 << bit) != 0 {
+                    offset |= (delta.next()? as usize) << (bit * 8);
+                }
+            }
+            for bit in 0..3 {
+                if cmd & (0x10 << bit) != 0 {
+                    length |= (delta.next()? as usize) << (bit * 8);
+                }
+            }
+            let length = if length == 0 { 0x10000 } else { length };
+            out.extend_from_slice(base.get(offset..offset.checked_add(length)?)?);
+        } else if cmd != 0 {
+            for _ in 0..cmd {
+                out.push(delta.next()?);
+            }
+        } else {
+            return None;
+        }
+
+        if out.len() > size {
+            return None;
+        }
+    }
+
+    (out.len() == size).then_some(out)
+}
+
+fn varint(bytes: &mut impl Iterator<Item = u8>) -> Option<usize> {
+    let mut value = 0usize;
+    let mut shift = 0;
+    while shift < usize::BITS {
+        let byte = bytes.next()?;
+        value |= ((byte & 0x7f) as usize) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::apply;
+
+    #[test]
+    fn apply_delta() {
+        let base = b"hello world";
+        assert_eq!(apply(base, &[11, 8, 0x90, 5, 3, b'!', b'!', b'!']).unwrap(), b"hello!!!");
+        assert_eq!(apply(base, &[11, 5, 0x91, 6, 5]).unwrap(), b"world");
+    }
+
+    #[test]
+    fn reject_bad_delta() {
+        let base = b"hello world";
+        assert!(apply(base, &[10, 5, 0x90, 5]).is_none());
+        assert!(apply(base, &[11, 4, 0x90, 5]).is_none());
+        assert!(apply(base, &[11, 6, 0x90, 5]).is_none());
+        assert!(apply(base, &[11, 5, 0x91, 8, 5]).is_none());
+        assert!(apply(base, &[11, 5, 0x00]).is_none());
+        assert!(apply(base, &[11, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 0x01, b'x']).is_none());
+    }
+}
@@ -0,0 +1,502 @@
+use std::env;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use crate::System;
+use super::{Commit, Error, Kind, Object, Oid, Tag};
+use super::pack::{apply, inflate, Entry, Pack};
+
+const ALTERNATES: usize = 8;
+const DEPTH:      usize = 4096;
+
+#[derive(Debug)]
+pub struct Repository<S> {
+    git:     PathBuf,
+    common:  PathBuf,
+    objects: Vec<PathBuf>,
+    packs:   Vec<Pack>,
+    sys:     PhantomData<S>,
+}
+
+enum Location<'a> {
+    Loose(Object),
+    Packed(&'a Pack, u64),
+}
+
+impl<S: System> Repository<S> {
+    pub fn discover(start: &Path) -> Option<Self> {
+        if let Some(git) = env::var_os("GIT_DIR") {
+            return Some(Self::open(start.join(git)));
+        }
+
+        start.ancestors().find_map(|dir| {
+            let git = dir.join(".git");
+            if read::<S>(&git.join("HEAD")).is_some() {
+                return Some(Self::open(git));
+            }
+            let link = read::<S>(&git)?;
+            let link = link.strip_prefix("gitdir:")?.trim();
+            Some(Self::open(dir.join(link)))
+        })
+    }
+
+    pub fn config(&self) -> PathBuf {
+        self.common.join("config")
+    }
+
+    pub fn read(&self, oid: &Oid) -> Result<Object, Error> {
+        let mut location = self.locate(oid)?;
+        let mut deltas   = Vec::new();
+
+        let mut object = loop {
+            if deltas.len() >= DEPTH {
+                return Err(Error::Corrupt(*oid));
+            }
+
+            let (pack, offset) = match location {
+                Location::Loose(object)        => break object,
+                Location::Packed(pack, offset) => (pack, offset),
+            };
+
+            let (entry, data) = pack.entry::<S>(offset)?;
+            location = match entry {
+                Entry::Base(kind)   => break Object { kind, data },
+                Entry::Offset(base) => Location::Packed(pack, base),
+                Entry::Ref(base)    => self.locate(&base)?,
+            };
+            deltas.push(data);
+        };
+
+        for delta in deltas.iter().rev() {
+            object.data = apply(&object.data, delta).ok_or(Error::Corrupt(*oid))?;
+        }
+
+        Ok(object)
+    }
+
+    pub fn commit(&self, oid: &Oid) -> Result<Commit, Error> {
+        Commit::parse(*oid, &self.read(oid)?)
+    }
+
+    pub fn tag(&self, oid: &Oid) -> Result<Option<Tag>, Error> {
+        let object = self.read(oid)?;
+        match object.kind {
+            Kind::Tag => Ok(Some(Tag::parse(*oid, &object)?)),
+            _         => Ok(None),
+        }
+    }
+
+    pub fn peel(&self, oid: &Oid) -> Result<Oid, Error> {
+        let mut oid = *oid;
+        while let Some(tag) = self.tag(&oid)? {
+            oid = tag.target;
+        }
+        Ok(oid)
+    }
+
+    pub fn resolve(&self, rev: &str) -> Result<Oid, Error> {
+        let unknown = || Error::Revision(rev.to_owned());
+
+        let end = rev.find(['~', '^']).unwrap_or(rev.len());
+        let (name, mut suffix) = rev.split_at(end);
+
+        let mut oid = self.name(name)?.ok_or_else(unknown)?;
+
+        while let Some(op) = suffix.chars().next() {
+            if op != '~' && op != '^' {
+                return Err(unknown());
+            }
+            suffix = &suffix[1..];
+
+            if let Some(rest) = suffix.strip_prefix("{}").filter(|_| op == '^') {
+                oid    = self.peel(&oid)?;
+                suffix = rest;
+                continue;
+            }
+
+            let end = suffix.find(|c: char| !c.is_ascii_digit()).unwrap_or(suffix.len());
+            let n   = match &suffix[..end] {
+                ""     => 1,
+                digits => digits.parse().map_err(|_| unknown())?,
+            };
+            suffix = &suffix[end..];
+
+            oid = self.peel(&oid)?;
+            oid = match op {
+                '~' => (0..n).try_fold(oid, |oid, _| {
+                    self.commit(&oid)?.parents.first().copied().ok_or_else(unknown)
+                })?,
+                _   => match n {
+                    0 => oid,
+                    n => self.commit(&oid)?.parents.get(n - 1).copied().ok_or_else(unknown)?,
+                },
+            };
+        }
+
+        Ok(oid)
+    }
+
+    pub fn tags(&self) -> Vec<(String, Oid)> {
+        let mut tags = self.packed().into_iter().filter(|(name, _)| {
+            name.starts_with("refs/tags/")
+        }).collect::<Vec<_>>();
+
+        let mut dirs = vec![self.common.join("refs").join("tags")];
+        while let Some(dir) = dirs.pop() {
+            for path in S::list(&dir).unwrap_or_default() {
+                match read::<S>(&path) {
+                    Some(data) => if let Ok(oid) = data.trim().parse() {
+                        let name = path.strip_prefix(&self.common).unwrap_or(&path);
+                        let name = name.to_string_lossy().replace('\\', "/");
+                        tags.retain(|(tag, _)| *tag != name);
+                        tags.push((name, oid));
+                    },
+                    None       => dirs.push(path),
+                }
+            }
+        }
+
+        tags.sort();
+        tags
+    }
+
+    fn open(git: PathBuf) -> Self {
+        let common = read::<S>(&git.join("commondir")).map(|dir| {
+            git.join(dir.trim())
+        }).unwrap_or_else(|| git.clone());
+
+        let mut objects = vec![common.join("objects")];
+        let mut index   = 0;
+        while index < objects.len() && objects.len() < ALTERNATES {
+            let dir  = objects[index].join("info").join("alternates");
+            let list = read::<S>(&dir).unwrap_or_default();
+            let list = list.lines().filter(|line| {
+                !line.is_empty() && !line.starts_with('#')
+            }).map(|line| objects[index].join(line)).collect::<Vec<_>>();
+            objects.extend(list);
+            index += 1;
+        }
+
+        let packs = objects.iter().flat_map(|dir| {
+            S::list(&dir.join("pack")).unwrap_or_default()
+        }).filter(|path| {
+            path.extension().is_some_and(|ext| ext == "idx")
+        }).filter_map(|idx| Pack::open::<S>(&idx).ok()).collect();
+
+        Self { git, common, objects, packs, sys: PhantomData }
+    }
+
+    fn locate<'a>(&'a self, oid: &Oid) -> Result<Location<'a>, Error> {
+        for dir in &self.objects {
+            let hex  = oid.to_string();
+            let path = dir.join(&hex[..2]).join(&hex[2..]);
+            if let Ok(data) = S::read(&path.into()) {
+                return loose(&data).map(Location::Loose).ok_or(Error::Corrupt(*oid));
+            }
+        }
+
+        for pack in &self.packs {
+            if let Some(offset) = pack.find(oid) {
+                return Ok(Location::Packed(pack, offset));
+            }
+        }
+
+        Err(Error::Missing(*oid))
+    }
+
+    fn name(&self, name: &str) -> Result<Option<Oid>, Error> {
+        let name = match name {
+            "" | "@" => "HEAD",
+            name     => name,
+        };
+
+        if let Ok(oid) = name.parse() {
+            return Ok(Some(oid));
+        }
+
+        let candidates = [
+            name.to_owned(),
+            format!("refs/{name}"),
+            format!("refs/tags/{name}"),
+            format!("refs/heads/{name}"),
+            format!("refs/remotes/{name}"),
+            format!("refs/remotes/{name}/HEAD"),
+        ];
+
+        if let Some(oid) = candidates.iter().find_map(|name| self.reference(name, 0)) {
+            return Ok(Some(oid));
+        }
+
+        self.abbreviated(name)
+    }
+
+    fn reference(&self, name: &str, depth: usize) -> Option<Oid> {
+        let data = read::<S>(&self.git.join(name)).or_else(|| {
+            read::<S>(&self.common.join(name))
+        });
+
+        let Some(data) = data else {
+            return self.packed().into_iter().find(|(r, _)| r == name).map(|(_, oid)| oid);
+        };
+
+        match data.trim().strip_prefix("ref:") {
+            Some(name) if depth < 5 => self.reference(name.trim(), depth + 1),
+            Some(_)                 => None,
+            None                    => data.trim().parse().ok(),
+        }
+    }
+
+    fn packed(&self) -> Vec<(String, Oid)> {
+        let refs = read::<S>(&self.common.join("packed-refs")).unwrap_or_default();
+        refs.lines().filter_map(|line| {
+            let (oid, name) = line.split_once(' ')?;
+            Some((name.to_owned(), oid.parse().ok()?))
+        }).collect()
+    }
+
+    fn abbreviated(&self, prefix: &str) -> Result<Option<Oid>, Error> {
+        if prefix.len() < 4 || !prefix.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Ok(None);
+        }
+
+        let prefix = prefix.to_ascii_lowercase();
+
+        let mut found = self.packs.iter().flat_map(|pack| {
+            pack.prefixed(&prefix).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+
+        for dir in &self.objects {
+            let files = S::list(&dir.join(&prefix[..2])).unwrap_or_default();
+            found.extend(files.iter().filter_map(|path| {
+                let name = path.file_name()?.to_str()?;
+                format!("{}{name}", &prefix[..2]).parse().ok()
+            }).filter(|oid: &Oid| oid.to_string().starts_with(&prefix)));
+        }
+
+        found.sort();
+        found.dedup();
+
+        match found[..] {
+            [oid] => Ok(Some(oid)),
+            []    => Ok(None),
+            [..]  => Err(Error::Revision(prefix)),
+        }
+    }
+}
+
+fn loose(data: &[u8]) -> Option<Object> {
+    let data = inflate(data)?;
+    let nul  = data.iter().position(|b| *b == 0)?;
+    let kind = data[..nul].split(|b| *b == b' ').next().and_then(Kind::parse)?;
+    Some(Object { kind, data: data[nul + 1..].to_vec() })
+}
+
+fn read<S: System>(path: &Path) -> Option<String> {
+    let data = S::read(&path.to_owned().into()).ok()?;
+    String::from_utf8(data).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::io::Write;
+    use std::path::Path;
+    use std::process::Command;
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+    use tempfile::TempDir;
+    use crate::platform::system::LocalSystem;
+    use super::super::{Error, Kind, Oid};
+    use super::Repository;
+
+    fn git(dir: &Path, args: &[&str]) -> Vec<u8> {
+        let output = Command::new("git").args(args).current_dir(dir)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_AUTHOR_NAME", "alice")
+            .env("GIT_AUTHOR_EMAIL", "alice@example.com")
+            .env("GIT_AUTHOR_DATE", "1700000000 +0000")
+            .env("GIT_COMMITTER_NAME", "alice")
+            .env("GIT_COMMITTER_EMAIL", "alice@example.com")
+            .env("GIT_COMMITTER_DATE", "1700000000 +0000")
+            .output().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        output.stdout
+    }
+
+    fn rev(dir: &Path, rev: &str) -> Oid {
+        String::from_utf8(git(dir, &["rev-parse", rev])).unwrap().trim().parse().unwrap()
+    }
+
+    fn fixture() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path();
+
+        git(dir, &["init", "-q", "-b", "main"]);
+
+        let mut text = (0..200).map(|n| format!("line {n}\n")).collect::<String>();
+        for n in 0..4 {
+            text.push_str(&format!("change {n}\n"));
+            fs::write(dir.join("file"), &text).unwrap();
+            git(dir, &["add", "file"]);
+            git(dir, &["commit", "-q", "-m", &format!("commit {n}")]);
+        }
+
+        git(dir, &["checkout", "-q", "-b", "side", "main~2"]);
+        fs::write(dir.join("side"), "side\n").unwrap();
+        git(dir, &["add", "side"]);
+        git(dir, &["commit", "-q", "-m", "side"]);
+        git(dir, &["checkout", "-q", "main"]);
+        git(dir, &["merge", "-q", "--no-ff", "-m", "merge", "side"]);
+        git(dir, &["tag", "-a", "-m", "release", "v1", "main~1"]);
+        git(dir, &["tag", "light", "side"]);
+
+        tmp
+    }
+
+    fn open(dir: &Path) -> Repository<LocalSystem> {
+        Repository::open(dir.join(".git"))
+    }
+
+    fn objects(dir: &Path) -> BTreeMap<Oid, (Kind, Vec<u8>)> {
+        let out = git(dir, &["cat-file", "--batch-all-objects", "--batch"]);
+        let mut objects = BTreeMap::new();
+        let mut rest    = &out[..];
+        while let Some(end) = rest.iter().position(|b| *b == b'\n') {
+            let header = std::str::from_utf8(&rest[..end]).unwrap();
+            let fields = header.split(' ').collect::<Vec<_>>();
+            let size   = fields[2].parse::<usize>().unwrap();
+            let data   = rest[end + 1..end + 1 + size].to_vec();
+            let kind   = Kind::parse(fields[1].as_bytes()).unwrap();
+            objects.insert(fields[0].parse().unwrap(), (kind, data));
+            rest = &rest[end + 2 + size..];
+        }
+        objects
+    }
+
+    fn check(dir: &Path) {
+        let repo    = open(dir);
+        let objects = objects(dir);
+        assert!(objects.len() > 10);
+        for (oid, (kind, data)) in objects {
+            let object = repo.read(&oid).unwrap();
+            assert_eq!(object.kind, kind, "{oid}");
+            assert_eq!(object.data, data, "{oid}");
+        }
+    }
+
+    fn deltas(dir: &Path) -> usize {
+        let pack = fs::read_dir(dir.join(".git/objects/pack")).unwrap().map(|entry| {
+            entry.unwrap().path()
+        }).find(|path| path.extension().is_some_and(|ext| ext == "idx")).unwrap();
+        let out = git(dir, &["verify-pack", "-v", pack.to_str().unwrap()]);
+        String::from_utf8(out).unwrap().lines().filter(|line| {
+            line.split_whitespace().count() == 7
+        }).count()
+    }
+
+    #[test]
+    fn loose_objects() {
+        let tmp = fixture();
+        check(tmp.path());
+    }
+
+    #[test]
+    fn offset_deltas() {
+        let tmp = fixture();
+        let dir = tmp.path();
+        git(dir, &["repack", "-q", "-a", "-d", "-f", "--depth=50"]);
+        git(dir, &["prune-packed"]);
+        assert!(deltas(dir) > 0);
+        assert!(!fs::read_dir(dir.join(".git/objects")).unwrap().any(|entry| {
+            entry.unwrap().file_name().len() == 2
+        }));
+        check(dir);
+    }
+
+    #[test]
+    fn ref_deltas() {
+        let tmp = fixture();
+        let dir = tmp.path();
+        git(dir, &["-c", "repack.useDeltaBaseOffset=false", "repack", "-q", "-a", "-d", "-f", "--depth=50"]);
+        git(dir, &["prune-packed"]);
+        assert!(deltas(dir) > 0);
+        check(dir);
+    }
+
+    #[test]
+    fn revisions() {
+        let tmp  = fixture();
+        let dir  = tmp.path();
+        let repo = open(dir);
+
+        for rev in ["HEAD", "@", "main", "side", "HEAD~1", "HEAD~3", "HEAD^2", "HEAD^2~1", "HEAD^^", "v1", "v1^{}", "v1~1", "light", "refs/tags/v1"] {
+            assert_eq!(repo.resolve(rev).unwrap(), self::rev(dir, rev), "{rev}");
+        }
+
+        let head = rev(dir, "HEAD").to_string();
+        assert_eq!(repo.resolve(&head[..8]).unwrap(), rev(dir, "HEAD"));
+
+        assert!(matches!(repo.resolve("HEAD~1é"), Err(Error::Revision(_))));
+        assert!(matches!(repo.resolve("HEAD^3"), Err(Error::Revision(_))));
+        assert!(matches!(repo.resolve("missing"), Err(Error::Revision(_))));
+    }
+
+    #[test]
+    fn packed_refs() {
+        let tmp = fixture();
+        let dir = tmp.path();
+        git(dir, &["pack-refs", "--all"]);
+        assert!(!dir.join(".git/refs/tags/v1").exists());
+
+        let repo = open(dir);
+        for rev in ["main", "side", "v1", "v1^{}", "light"] {
+            assert_eq!(repo.resolve(rev).unwrap(), self::rev(dir, rev), "{rev}");
+        }
+
+        let tags = repo.tags().into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        assert_eq!(tags, ["refs/tags/light", "refs/tags/v1"]);
+    }
+
+    fn hostile(header: &[u8]) -> (TempDir, Oid) {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join(".git").join("objects").join("pack");
+        fs::create_dir_all(&dir).unwrap();
+
+        let oid = Oid::from_bytes(&[0x11; Oid::LEN]).unwrap();
+
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(&[1, 1, 0x01, b'x']).unwrap();
+
+        let mut pack = b"PACK\0\0\0\x02\0\0\0\x01".to_vec();
+        pack.extend(header);
+        pack.extend(zlib.finish().unwrap());
+
+        let mut index = b"\xfftOc\0\0\0\x02".to_vec();
+        for n in 0..256 {
+            index.extend(match n < 0x11 { true => 0u32, false => 1 }.to_be_bytes());
+        }
+        index.extend(oid.as_bytes());
+        index.extend(0u32.to_be_bytes());
+        index.extend(12u32.to_be_bytes());
+
+        fs::write(dir.join("pack-hostile.pack"), pack).unwrap();
+        fs::write(dir.join("pack-hostile.idx"), index).unwrap();
+
+        (tmp, oid)
+    }
+
+    #[test]
+    fn self_offset_delta() {
+        let (tmp, oid) = hostile(&[0x64, 0x00]);
+        assert!(open(tmp.path()).read(&oid).is_err());
+    }
+
+    #[test]
+    fn self_ref_delta() {
+        let mut header = vec![0x74];
+        header.extend([0x11; Oid::LEN]);
+        let (tmp, oid) = hostile(&header);
+        assert!(matches!(open(tmp.path()).read(&oid), Err(Error::Corrupt(_))));
+    }
+}
@@ -1,15 +1,31 @@
 use std::fmt;
 use std::ops::Deref;
+use std::str::FromStr;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use ssh_key::{HashAlg, PrivateKey};
+use rand_core::OsRng;
+use md5::{Digest, Md5};
+use ssh_key::{Algorithm, EcdsaCurve, Fingerprint, HashAlg, PrivateKey, PublicKey};
+use ssh_key::private::{KeypairData, RsaKeypair};
+use ssh_key::public::KeyData;
+use time::OffsetDateTime;
+use crate::Succession;
+use crate::timestamp::{self, format};
 use super::{Password, Provider};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Keychain {
-    keychain: IndexMap<String, Key>,
+    keychain: IndexMap<String, Entry>,
     metadata: Metadata,
     provider: Provider,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    signers:  IndexMap<String, Principals>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    validity: IndexMap<String, Validity>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    retired:  IndexMap<String, Succession>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    revoked:  IndexMap<String, Revoked>,
 }
 
 #[derive(Clone, Debug)]
@@ -17,16 +33,97 @@ pub enum Key {
     SSH(PrivateKey),
 }
 
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub key:     Key,
+    pub details: Details,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Details {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label:   String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub comment: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Metadata {
     pub identity: String,
     pub password: Password,
+    #[serde(default)]
+    pub expiry:   Expiry,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Expiry {
+    #[default]
+    Refuse,
+    Warn,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Principals {
+    pub principals:   Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces:   Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_after:  Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_before: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum KeyType {
+    #[default]
+    Ed25519,
+    EcdsaP256,
+    EcdsaP384,
+    Rsa3072,
+    Rsa4096,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Validity {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_after: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires:     Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Revoked {
+    pub revoked: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason:  String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key:     Option<PublicKey>,
+}
+
+enum Selector<'a> {
+    Key(PublicKey),
+    Md5(&'a str),
+    Fingerprint(Fingerprint),
+    Id(String),
+    Name(&'a str),
 }
 
 #[derive(Debug)]
 pub enum Error {
     KeyNotFound,
-    KeyAmbiguous,
+    KeyAmbiguous(Vec<String>),
+    KeyExpired(OffsetDateTime),
+    KeyNotYetValid(OffsetDateTime),
+    KeyRetired(String),
+    KeyRevoked(String),
+    WeakKey(String),
+    InvalidExpiry(String),
+    InvalidKeyType(String),
+    InvalidSelector(String),
+    InvalidLabel(String),
 }
 
 impl Keychain {
@@ -35,30 +132,126 @@ impl Keychain {
             keychain: IndexMap::new(),
             metadata: metadata,
             provider: provider,
+            signers:  IndexMap::new(),
+            validity: IndexMap::new(),
+            retired:  IndexMap::new(),
+            revoked:  IndexMap::new(),
         }
     }
 
     pub fn add(&mut self, key: impl Into<Key>) -> String {
-        let key = key.into();
-        let id  = key.id();
-        self.keychain.insert(id.clone(), key);
+        let key     = key.into();
+        let id      = key.id();
+        let details = Details::default();
+        self.keychain.insert(id.clone(), Entry { key, details });
         id
     }
 
+    pub fn describe(&mut self, key: &str, details: Details) {
+        if let Some(entry) = self.keychain.get_mut(key) {
+            entry.details = details;
+        }
+    }
+
+    pub fn details(&self, key: &str) -> Option<&Details> {
+        self.keychain.get(key).map(|entry| &entry.details)
+    }
+
     pub fn delete(&mut self, key: &str) -> Option<Key> {
-        self.keychain.remove(key)
+        self.signers.remove(key);
+        self.validity.remove(key);
+        self.retired.remove(key);
+        let entry = self.keychain.remove(key)?;
+        if let Some(revoked) = self.revoked.get_mut(key) {
+            revoked.key.get_or_insert_with(|| entry.key.public_key().clone());
+        }
+        Some(entry.key)
+    }
+
+    pub fn allow(&mut self, key: &str, principals: Principals) {
+        self.signers.insert(key.to_owned(), principals);
+    }
+
+    pub fn disallow(&mut self, key: &str) {
+        self.signers.remove(key);
+    }
+
+    pub fn principals(&self, key: &str) -> Option<&Principals> {
+        self.signers.get(key)
+    }
+
+    pub fn limit(&mut self, key: &str, validity: Validity) {
+        self.validity.insert(key.to_owned(), validity);
+    }
+
+    pub fn validity(&self, key: &str) -> Option<&Validity> {
+        self.validity.get(key)
+    }
+
+    pub fn retire(&mut self, key: &str, succession: Succession) {
+        self.retired.insert(key.to_owned(), succession);
+    }
+
+    pub fn successor(&self, key: &str) -> Option<&Succession> {
+        self.retired.get(key)
+    }
+
+    pub fn successions(&self) -> impl Iterator<Item = &Succession> {
+        self.retired.values()
+    }
+
+    pub fn revoke(&mut self, key: &str, revoked: Revoked) {
+        self.revoked.insert(key.to_owned(), revoked);
+    }
+
+    pub fn revocation(&self, key: &str) -> Option<&Revoked> {
+        self.revoked.get(key)
+    }
+
+    pub fn revocations(&self) -> impl Iterator<Item = (&String, &Revoked)> {
+        self.revoked.iter()
     }
 
-    pub fn find(&self, prefix: &str) -> Result<&Key, Error> {
-        let keys = self.keychain.iter().filter_map(|(id, key)| {
-            id.starts_with(prefix).then_some(key)
-        }).collect::<Vec<_>>();
+    pub fn usable(&self, key: &str) -> Result<(), Error> {
+        if let Some(revoked) = self.revoked.get(key) {
+            return Err(Error::KeyRevoked(revoked.reason.clone()));
+        }
 
-        match keys[..] {
-            [key] => Ok(key),
-            []    => Err(Error::KeyNotFound),
-            [..]  => Err(Error::KeyAmbiguous),
+        if let Some(succession) = self.retired.get(key) {
+            let fingerprint = succession.new.fingerprint(HashAlg::Sha256);
+            return Err(Error::KeyRetired(hex::encode(fingerprint)));
         }
+
+        Ok(())
+    }
+
+    pub fn expiry(&mut self, expiry: Expiry) {
+        self.metadata.expiry = expiry;
+    }
+
+    pub fn find(&self, selector: &str) -> Result<&Key, Error> {
+        let entries = match Selector::parse(selector)? {
+            Selector::Name(name) => match self.matching(|_, entry| entry.details.label == name) {
+                entries if entries.is_empty() => self.matching(|_, entry| entry.details.comment == name),
+                entries                       => entries,
+            },
+            selector             => self.matching(|id, entry| selector.matches(id, entry)),
+        };
+
+        match entries[..] {
+            [(_, entry)] => Ok(&entry.key),
+            []           => Err(Error::KeyNotFound),
+            [..]         => Err(Error::KeyAmbiguous(entries.iter().map(|(id, entry)| {
+                match entry.details.label.as_str() {
+                    ""    => id[..16].to_owned(),
+                    label => format!("{} ({label})", &id[..16]),
+                }
+            }).collect())),
+        }
+    }
+
+    pub fn key(&self, key: &str) -> Option<&Key> {
+        self.keychain.get(key).map(|entry| &entry.key)
     }
 
     pub fn metadata(&self) -> &Metadata {
@@ -69,8 +262,21 @@ impl Keychain {
         &self.metadata.password
     }
 
+    pub fn rekey(&mut self, password: Password, keys: Vec<Key>) -> Password {
+        for key in keys {
+            if let Some(entry) = self.keychain.get_mut(&key.id()) {
+                entry.key = key;
+            }
+        }
+        std::mem::replace(&mut self.metadata.password, password)
+    }
+
     pub fn keys(&self) -> impl Iterator<Item = &Key> {
-        self.keychain.values()
+        self.keychain.values().map(|entry| &entry.key)
+    }
+
+    fn matching(&self, f: impl Fn(&str, &Entry) -> bool) -> Vec<(&String, &Entry)> {
+        self.keychain.iter().filter(|(id, entry)| f(id, entry)).collect()
     }
 }
 
@@ -83,6 +289,136 @@ impl Key {
     }
 }
 
+impl Details {
+    pub fn is_empty(&self) -> bool {
+        let Self { label, comment, created } = self;
+        label.is_empty() && comment.is_empty() && created.is_none()
+    }
+}
+
+impl KeyType {
+    pub fn generate(self) -> Result<PrivateKey, ssh_key::Error> {
+        let ecdsa = |curve| Algorithm::Ecdsa { curve };
+        let rsa   = |bits| -> Result<PrivateKey, ssh_key::Error> {
+            let key = RsaKeypair::random(OsRng, bits)?;
+            PrivateKey::new(KeypairData::from(key), "")
+        };
+
+        match self {
+            Self::Ed25519   => PrivateKey::random(OsRng, Algorithm::Ed25519),
+            Self::EcdsaP256 => PrivateKey::random(OsRng, ecdsa(EcdsaCurve::NistP256)),
+            Self::EcdsaP384 => PrivateKey::random(OsRng, ecdsa(EcdsaCurve::NistP384)),
+            Self::Rsa3072   => rsa(3072),
+            Self::Rsa4096   => rsa(4096),
+        }
+    }
+
+    pub fn of(key: &KeyData) -> Self {
+        match key {
+            KeyData::Ecdsa(key) if key.curve() == EcdsaCurve::NistP256 => Self::EcdsaP256,
+            KeyData::Ecdsa(key) if key.curve() == EcdsaCurve::NistP384 => Self::EcdsaP384,
+            KeyData::Rsa(_) if bits(key) <= 3072                       => Self::Rsa3072,
+            KeyData::Rsa(_)                                            => Self::Rsa4096,
+            _                                                          => Self::Ed25519,
+        }
+    }
+}
+
+impl<'a> Selector<'a> {
+    fn parse(selector: &'a str) -> Result<Self, Error> {
+        let key = selector.strip_prefix("key::").unwrap_or(selector);
+        if let Ok(key) = PublicKey::from_openssh(key) {
+            return Ok(Self::Key(key));
+        }
+
+        if let Some(hash) = selector.strip_prefix("MD5:") {
+            return Ok(Self::Md5(hash));
+        }
+
+        if let Ok(fingerprint) = selector.parse() {
+            return Ok(Self::Fingerprint(fingerprint));
+        }
+
+        match selector {
+            ""                    => Err(Error::InvalidSelector(selector.to_owned())),
+            id if hexadecimal(id) => Ok(Self::Id(id.to_ascii_lowercase())),
+            name                  => Ok(Self::Name(name)),
+        }
+    }
+
+    fn matches(&self, id: &str, entry: &Entry) -> bool {
+        let key = entry.key.public_key();
+        match self {
+            Self::Key(public)     => public.key_data() == key.key_data(),
+            Self::Md5(hash)       => md5(key).eq_ignore_ascii_case(hash),
+            Self::Fingerprint(fp) => key.fingerprint(fp.algorithm()) == *fp,
+            Self::Id(prefix)      => id.starts_with(prefix),
+            Self::Name(name)      => entry.details.label == *name,
+        }
+    }
+}
+
+pub fn label(label: &str) -> Result<(), Error> {
+    let selector = hexadecimal(label) || label.contains(':') || label.ends_with(".pub");
+    let selector = selector || label.contains(std::path::is_separator) || PublicKey::from_openssh(label).is_ok();
+    match selector {
+        true  => Err(Error::InvalidLabel(label.to_owned())),
+        false => Ok(()),
+    }
+}
+
+fn hexadecimal(str: &str) -> bool {
+    !str.is_empty() && str.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+fn md5(key: &PublicKey) -> String {
+    let blob = key.to_bytes().unwrap_or_default();
+    Md5::digest(blob).iter().map(|byte| {
+        format!("{byte:02x}")
+    }).collect::<Vec<_>>().join(":")
+}
+
+pub fn strong(key: &KeyData) -> Result<(), Error> {
+    match key {
+        KeyData::Dsa(_)                     => Err(Error::WeakKey("DSA".to_owned())),
+        KeyData::Rsa(_) if bits(key) < 2048 => Err(Error::WeakKey(format!("RSA-{}", bits(key)))),
+        _                                   => Ok(()),
+    }
+}
+
+fn bits(key: &KeyData) -> usize {
+    let n = match key {
+        KeyData::Rsa(key) => key.n.as_positive_bytes().unwrap_or_default(),
+        _                 => &[],
+    };
+    match n.first() {
+        Some(byte) => n.len() * 8 - byte.leading_zeros() as usize,
+        None       => 0,
+    }
+}
+
+impl Validity {
+    pub fn is_empty(&self) -> bool {
+        self.valid_after.is_none() && self.expires.is_none()
+    }
+
+    pub fn after(&self) -> Option<OffsetDateTime> {
+        self.valid_after.as_deref().and_then(timestamp::parse)
+    }
+
+    pub fn until(&self) -> Option<OffsetDateTime> {
+        self.expires.as_deref().and_then(timestamp::parse)
+    }
+
+    pub fn check(&self, time: OffsetDateTime) -> Result<(), Error> {
+        match (self.after(), self.until()) {
+            (Some(after), _) if time < after  => Err(Error::KeyNotYetValid(after)),
+            (_, Some(until)) if time >= until => Err(Error::KeyExpired(until)),
+            _                                 => Ok(()),
+        }
+    }
+}
+
 impl Deref for Key {
     type Target = PrivateKey;
 
@@ -98,13 +434,155 @@ impl From<PrivateKey> for Key {
     }
 }
 
+impl FromStr for KeyType {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str {
+            "ed25519"    => Ok(Self::Ed25519),
+            "ecdsa-p256" => Ok(Self::EcdsaP256),
+            "ecdsa-p384" => Ok(Self::EcdsaP384),
+            "rsa-3072"   => Ok(Self::Rsa3072),
+            "rsa-4096"   => Ok(Self::Rsa4096),
+            other        => Err(Error::InvalidKeyType(other.to_owned())),
+        }
+    }
+}
+
+impl FromStr for Expiry {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str {
+            "refuse" => Ok(Self::Refuse),
+            "warn"   => Ok(Self::Warn),
+            other    => Err(Error::InvalidExpiry(other.to_owned())),
+        }
+    }
+}
+
 impl std::error::Error for Error {}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::KeyNotFound   => write!(f, "key not found"),
-            Self::KeyAmbiguous  => write!(f, "key ambiguous"),
+            Self::KeyNotFound          => write!(f, "key not found"),
+            Self::KeyAmbiguous(keys)   => write!(f, "key ambiguous, matches {}", keys.join(", ")),
+            Self::KeyExpired(time)     => write!(f, "key expired at {}", format(*time)),
+            Self::KeyNotYetValid(time) => write!(f, "key not valid until {}", format(*time)),
+            Self::KeyRetired(id)       => write!(f, "key retired, succeeded by {id}"),
+            Self::KeyRevoked(reason)   => match reason.is_empty() {
+                true  => write!(f, "key is revoked"),
+                false => write!(f, "key is revoked: {reason}"),
+            },
+            Self::WeakKey(kind)        => write!(f, "refusing weak {kind} key"),
+            Self::InvalidExpiry(mode)  => write!(f, "invalid expiry mode '{mode}'"),
+            Self::InvalidKeyType(kind) => write!(f, "invalid key type '{kind}'"),
+            Self::InvalidSelector(key) => write!(f, "invalid key selector '{key}'"),
+            Self::InvalidLabel(label)  => write!(f, "invalid label '{label}', a label cannot look like a key id, fingerprint, public key or path"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use rand_core::OsRng;
+    use ssh_key::{Algorithm, EcdsaCurve, HashAlg, PrivateKey, PublicKey};
+    use tempfile::TempDir;
+    use crate::{Details, Expiry, Keychain, KeyType, Metadata, Password, Provider};
+    use super::{bits, label, strong, Error};
+
+    fn keychain() -> Keychain {
+        Keychain::new(Metadata {
+            identity: "default".to_owned(),
+            password: Password::insecure("test"),
+            expiry:   Expiry::default(),
+        }, Provider::Local)
+    }
+
+    fn details(label: &str, comment: &str) -> Details {
+        Details { label: label.to_owned(), comment: comment.to_owned(), created: None }
+    }
+
+    #[test]
+    fn selectors() {
+        let mut keychain = keychain();
+        let [one, two, three] = [(); 3].map(|_| keychain.add(PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap()));
+        keychain.describe(&one, details("work", &two[..8]));
+        keychain.describe(&two, details("laptop", "work"));
+        keychain.describe(&three, details("", "backup"));
+
+        let find = |selector: &str| keychain.find(selector).map(|key| key.id());
+        let key  = keychain.key(&one).unwrap().public_key();
+
+        assert_eq!(find(&one).unwrap(), one);
+        assert_eq!(find(&one[..6]).unwrap(), one);
+        assert_eq!(find(&one[..6].to_uppercase()).unwrap(), one);
+        assert_eq!(find(&key.fingerprint(HashAlg::Sha256).to_string()).unwrap(), one);
+        assert_eq!(find(&key.to_openssh().unwrap()).unwrap(), one);
+        assert_eq!(find(&format!("key::{}", key.to_openssh().unwrap())).unwrap(), one);
+        assert_eq!(find("work").unwrap(), one);
+        assert_eq!(find("laptop").unwrap(), two);
+        assert_eq!(find(&two[..8]).unwrap(), two);
+        assert_eq!(find("backup").unwrap(), three);
+
+        assert!(matches!(find(""), Err(Error::InvalidSelector(_))));
+        assert!(matches!(find("home"), Err(Error::KeyNotFound)));
+        assert!(matches!(find("SHA256:abc"), Err(Error::KeyNotFound)));
+
+        assert!(keychain.key(&one[..6]).is_none());
+        assert_eq!(keychain.key(&two).unwrap().id(), two);
+    }
+
+    #[test]
+    fn labels() {
+        for valid in ["work", "release key", "laptop-2024", "ab-cd"] {
+            assert!(label(valid).is_ok(), "{valid}");
+        }
+        for invalid in ["cafe", "1234", "SHA256:x", "MD5:00", "key::x", "keys/work", "work.pub"] {
+            assert!(matches!(label(invalid), Err(Error::InvalidLabel(_))), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn key_types() {
+        let ecdsa = |curve| Algorithm::Ecdsa { curve };
+        let types = [
+            ("ed25519",    KeyType::Ed25519,   Algorithm::Ed25519),
+            ("ecdsa-p256", KeyType::EcdsaP256, ecdsa(EcdsaCurve::NistP256)),
+            ("ecdsa-p384", KeyType::EcdsaP384, ecdsa(EcdsaCurve::NistP384)),
+        ];
+
+        for (name, kind, algorithm) in types {
+            assert_eq!(name.parse::<KeyType>().unwrap(), kind);
+            let key = kind.generate().unwrap();
+            assert_eq!(key.algorithm(), algorithm, "{name}");
+            assert_eq!(KeyType::of(key.public_key().key_data()), kind, "{name}");
+        }
+
+        assert_eq!("rsa-3072".parse::<KeyType>().unwrap(), KeyType::Rsa3072);
+        assert_eq!("rsa-4096".parse::<KeyType>().unwrap(), KeyType::Rsa4096);
+        assert!(matches!("rsa-1024".parse::<KeyType>(), Err(Error::InvalidKeyType(_))));
+    }
+
+    #[test]
+    fn rsa_sizes() {
+        let dir = TempDir::new().unwrap();
+        for (size, kind) in [(2048, KeyType::Rsa3072), (3072, KeyType::Rsa3072), (4096, KeyType::Rsa4096)] {
+            let path   = dir.path().join(format!("rsa-{size}"));
+            let status = Command::new("ssh-keygen")
+                .args(["-q", "-t", "rsa", "-b", &size.to_string(), "-N", "", "-f"])
+                .arg(&path)
+                .status()
+                .unwrap();
+            assert!(status.success());
+
+            let key  = PublicKey::read_openssh_file(&path.with_extension("pub")).unwrap();
+            let data = key.key_data();
+            assert_eq!(bits(data), size);
+            assert_eq!(KeyType::of(data), kind);
+            assert_eq!(strong(data).is_ok(), size >= 2048);
         }
     }
 }
@@ -1,12 +1,21 @@
+pub use keychain::Details;
+pub use keychain::Entry;
+pub use keychain::Error;
+pub use keychain::Expiry;
 pub use keychain::Key;
+pub use keychain::KeyType;
 pub use keychain::Keychain;
 pub use keychain::Metadata;
-pub use keychain::Error;
+pub use keychain::Principals;
+pub use keychain::Revoked;
+pub use keychain::Validity;
+pub use keychain::label;
+pub use keychain::strong;
 
 pub use password::Password;
 
 pub use provider::Provider;
 
 mod keychain;
-mod password;
+pub mod password;
 mod provider;
@@ -1,10 +1,12 @@
 use std::fmt;
+use std::io;
 use argon2::{Argon2, PasswordHasher, PasswordVerifier};
-use argon2::password_hash::{PasswordHash, SaltString};
+use argon2::password_hash::{self, PasswordHash, SaltString};
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroizing;
 use crate::{Secret, System};
+use crate::platform;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Password {
@@ -20,18 +22,26 @@ pub struct Static {
 
 #[derive(Debug)]
 pub enum Error {
-    Crypto(String),
-    System(String),
+    Incorrect,
+    Crypto(password_hash::Error),
+    Keyring(platform::Error),
+    System(io::Error),
     Mismatch,
 }
 
 impl Password {
     pub fn create<S: System>(secret: bool) -> Result<Self, Error> {
-        let password = request::<S>()?;
-        Ok(match secret {
-            true  => Self::Secret(Secret::create(password)?),
-            false => Self::Static(Static::create(password)?),
-        })
+        let (password, _) = Self::prompt::<S>(secret)?;
+        Ok(password)
+    }
+
+    pub fn prompt<S: System>(secret: bool) -> Result<(Self, Zeroizing<String>), Error> {
+        let value    = request::<S>()?;
+        let password = match secret {
+            true  => Self::Secret(Secret::create(value.clone())?),
+            false => Self::Static(Static::create(value.clone())?),
+        };
+        Ok((password, value))
     }
 
     pub fn lookup<S: System>(&self) -> Result<Zeroizing<String>, Error> {
@@ -42,6 +52,17 @@ impl Password {
         })
     }
 
+    pub fn is_secret(&self) -> bool {
+        matches!(self, Self::Secret(_))
+    }
+
+    pub fn discard(&self) -> Result<(), Error> {
+        match self {
+            Self::Secret(p) => Ok(p.delete()?),
+            _               => Ok(()),
+        }
+    }
+
     pub fn insecure(string: &str) -> Self {
         Self::String(string.to_owned())
     }
@@ -87,27 +108,32 @@ impl std::error::Error for Error {}
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Crypto(error) => write!(f, "{error}"),
-            Self::System(error) => write!(f, "{error}"),
-            Self::Mismatch      => write!(f, "password mismatch"),
+            Self::Incorrect      => write!(f, "incorrect password"),
+            Self::Crypto(error)  => write!(f, "{error}"),
+            Self::Keyring(error) => write!(f, "keyring unavailable: {error}"),
+            Self::System(error)  => write!(f, "{error}"),
+            Self::Mismatch       => write!(f, "password mismatch"),
         }
     }
 }
 
-impl From<std::io::Error> for Error {
-    fn from(error: std::io::Error) -> Self {
-        Self::System(error.to_string())
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::System(error)
     }
 }
 
-impl From<argon2::password_hash::Error> for Error {
-    fn from(error: argon2::password_hash::Error) -> Self {
-        Self::Crypto(error.to_string())
+impl From<password_hash::Error> for Error {
+    fn from(error: password_hash::Error) -> Self {
+        match error {
+            password_hash::Error::Password => Self::Incorrect,
+            error                          => Self::Crypto(error),
+        }
     }
 }
 
-impl From<crate::platform::Error> for Error {
-    fn from(error: crate::platform::Error) -> Self {
-        Self::System(error.to_string())
+impl From<platform::Error> for Error {
+    fn from(error: platform::Error) -> Self {
+        Self::Keyring(error)
     }
 }
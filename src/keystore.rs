@@ -19,6 +19,10 @@ impl Keystore {
         self.keychains.push(name.to_owned());
     }
 
+    pub fn keychains(&self) -> impl Iterator<Item = &str> {
+        self.keychains.iter().map(String::as_str)
+    }
+
     pub fn current(&self) -> Result<&str, Error> {
         match &self.current {
             Some(name) => Ok(name),
@@ -1,15 +1,33 @@
-#![allow(clippy::module_inception, clippy::redundant_field_names)]
+#![allow(clippy::module_inception, clippy::redundant_field_names, clippy::unit_arg)]
 
+pub use crate::contacts::Contact;
+pub use crate::contacts::Contacts;
+pub use crate::contacts::Source;
+pub use crate::contacts::Trust;
+
+pub use crate::keychain::Details;
+pub use crate::keychain::Expiry;
 pub use crate::keychain::Key;
+pub use crate::keychain::KeyType;
 pub use crate::keychain::Keychain;
 pub use crate::keychain::Metadata;
 pub use crate::keychain::Password;
+pub use crate::keychain::Principals;
 pub use crate::keychain::Provider;
+pub use crate::keychain::Revoked;
+pub use crate::keychain::Validity;
 
 pub use crate::keystore::Keystore;
 pub use crate::platform::signet;
 
+pub use crate::policy::Policy;
+pub use crate::policy::Signature;
+pub use crate::policy::Verdict;
+
+pub use crate::revocation::Revocations;
+
 pub use crate::secret::Secret;
+pub use crate::succession::Succession;
 pub use crate::signet::Signet;
 pub use crate::system::Input;
 pub use crate::system::System;
@@ -17,10 +35,18 @@ pub use crate::system::System;
 pub mod args;
 pub mod command;
 
+mod contacts;
+mod git;
 mod keychain;
 mod keystore;
 mod persist;
 mod platform;
+mod policy;
+mod revocation;
 mod secret;
+mod signers;
 mod signet;
+mod sshsig;
+mod succession;
 mod system;
+mod timestamp;
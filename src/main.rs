@@ -1,19 +1,30 @@
+use std::process::ExitCode;
 use anyhow::Result;
-use signet::signet;
+use signet::{signet, Signet, System};
 use signet::args::{args, Command};
-use signet::command::{init, keys, sign, verify};
+use signet::command::{compat, contacts, countersign, failure, git, init, keys, passwd, sign, verify, Format};
 
-fn main() -> Result<()> {
-    let (root, command) = args()?;
+fn main() -> Result<ExitCode> {
+    let (root, format, command) = args()?;
     let signet = signet(root);
 
+    Ok(run(&signet, format, command).unwrap_or_else(|error| {
+        failure(&error, format)
+    }))
+}
+
+fn run<S: System>(signet: &Signet<S>, format: Format, command: Command) -> Result<ExitCode> {
     match command {
-        Command::Init(cmd)   => init(&signet, cmd)?,
-        Command::Keys(cmd)   => keys(&signet, cmd)?,
-        Command::Sign(cmd)   => sign(&signet, cmd)?,
-        Command::Verify(cmd) => verify(&signet, cmd)?,
-        Command::Compat      => println!("COMPAT"),
+        Command::Init(cmd)         => init(signet, cmd, format)?,
+        Command::Passwd(cmd)       => passwd(signet, cmd, format)?,
+        Command::Keys(cmd)         => keys(signet, cmd, format)?,
+        Command::Sign(cmd)         => sign(signet, cmd, format)?,
+        Command::Verify(cmd)       => verify(signet, cmd, format)?,
+        Command::Countersign(cmd)  => countersign(signet, cmd)?,
+        Command::Contacts(cmd)     => contacts(signet, cmd)?,
+        Command::Git(cmd)          => git(signet, cmd)?,
+        Command::Compat(cmd, opts) => return Ok(compat(signet, cmd, &opts)),
     };
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -1,9 +1,10 @@
 use std::fmt;
-use serde::de::{Deserialize, Deserializer, Error, Visitor};
+use serde::de::{Deserialize, Deserializer, Error, MapAccess, Visitor};
 use serde::de::{EnumAccess, Expected, Unexpected, VariantAccess};
+use serde::de::value::MapAccessDeserializer;
 use serde::ser::{Serialize, Serializer};
 use ssh_key::{LineEnding, PrivateKey};
-use crate::keychain::Key;
+use crate::keychain::{Details, Entry, Key};
 
 impl<'de> Deserialize<'de> for Key {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
@@ -26,6 +27,39 @@ impl Serialize for Key {
     }
 }
 
+impl<'de> Deserialize<'de> for Entry {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_any(EntryVisitor)
+    }
+}
+
+impl Serialize for Entry {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        if self.details.is_empty() {
+            return self.key.serialize(s);
+        }
+
+        Stored {
+            key:     &self.key,
+            details: &self.details,
+        }.serialize(s)
+    }
+}
+
+#[derive(serde::Serialize)]
+struct Stored<'a> {
+    key:     &'a Key,
+    #[serde(flatten)]
+    details: &'a Details,
+}
+
+#[derive(serde::Deserialize)]
+struct Loaded {
+    key:     Key,
+    #[serde(flatten)]
+    details: Details,
+}
+
 struct KeyVisitor;
 
 impl<'de> Visitor<'de> for KeyVisitor {
@@ -50,6 +84,67 @@ impl<'de> Visitor<'de> for KeyVisitor {
     }
 }
 
+struct EntryVisitor;
+
+impl<'de> Visitor<'de> for EntryVisitor {
+    type Value = Entry;
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let key     = KeyVisitor.visit_enum(data)?;
+        let details = Details::default();
+        Ok(Entry { key, details })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let Loaded { key, details } = Loaded::deserialize(MapAccessDeserializer::new(map))?;
+        Ok(Entry { key, details })
+    }
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an OpenSSH private key and its details")
+    }
+}
+
 fn invalid<'de, A: EnumAccess<'de>>(str: &str, expected: &dyn Expected) -> A::Error {
     A::Error::invalid_value(Unexpected::Str(str), expected)
 }
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+    use serde_yaml::{from_str, to_string, Value};
+    use ssh_key::{Algorithm, PrivateKey};
+    use crate::{Details, Expiry, Keychain, Metadata, Password, Provider};
+
+    #[test]
+    fn entries() {
+        let mut keychain = Keychain::new(Metadata {
+            identity: "default".to_owned(),
+            password: Password::insecure("test"),
+            expiry:   Expiry::default(),
+        }, Provider::Local);
+
+        let [bare, described] = [(); 2].map(|_| {
+            keychain.add(PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap())
+        });
+        keychain.describe(&described, Details {
+            label:   "work".to_owned(),
+            comment: "work laptop".to_owned(),
+            created: Some("20240101000000Z".to_owned()),
+        });
+
+        let saved = to_string(&keychain).unwrap();
+        let value = from_str::<Value>(&saved).unwrap();
+        assert!(matches!(value["keychain"][bare.as_str()], Value::Tagged(_)));
+        assert_eq!(value["keychain"][described.as_str()]["label"].as_str(), Some("work"));
+        assert!(value["keychain"][described.as_str()].get("algorithm").is_none());
+        assert!(value.get("signers").is_none());
+
+        let loaded = from_str::<Keychain>(&saved).unwrap();
+        assert!(loaded.details(&bare).unwrap().is_empty());
+        let details = loaded.details(&described).unwrap();
+        assert_eq!((details.label.as_str(), details.comment.as_str()), ("work", "work laptop"));
+        assert_eq!(details.created.as_deref(), Some("20240101000000Z"));
+        assert_eq!(to_string(&loaded).unwrap(), saved);
+    }
+}
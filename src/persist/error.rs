@@ -11,7 +11,7 @@ pub struct Error {
 #[derive(Debug)]
 pub enum Cause {
     Invalid(serde_yaml::Error),
-    Missing(io::Error),
+    Missing(#[allow(dead_code)] io::Error),
     System(io::Error),
 }
 
@@ -28,6 +28,16 @@ impl<T, E: Into<Cause>> Context<T> for Result<T, E> {
     }
 }
 
+impl Error {
+    pub fn missing(&self) -> bool {
+        matches!(self.cause, Cause::Missing(_))
+    }
+
+    pub fn invalid(&self) -> bool {
+        matches!(self.cause, Cause::Invalid(_))
+    }
+}
+
 impl std::error::Error for Error {}
 
 impl fmt::Display for Error {
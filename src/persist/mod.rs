@@ -1,7 +1,6 @@
 pub use store::Persistent;
 pub use store::Store;
 
-pub use error::Cause;
 pub use error::Context;
 pub use error::Error;
 
@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_yaml::{from_slice, to_string};
-use crate::{Keychain, Keystore, System};
+use crate::{Contacts, Keychain, Keystore, Policy, System};
 use super::{Context, Error};
 
 pub struct Store<S> {
@@ -16,6 +16,10 @@ impl<S: System> Store<S> {
         Self { root, sys }
     }
 
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
     pub fn initialize(&self) -> Result<(), Error> {
         let path = &self.root.join("chains");
         S::mkdir(path).context(path)
@@ -29,6 +33,20 @@ impl<S: System> Store<S> {
         self.load(&keychain(name))
     }
 
+    pub fn contacts(&self) -> Result<Contacts, Error> {
+        match self.load(&contacts()) {
+            Err(e) if e.missing() => Ok(Contacts::default()),
+            result                => result,
+        }
+    }
+
+    pub fn policy(&self) -> Result<Option<Policy>, Error> {
+        match self.load(&policy()) {
+            Err(e) if e.missing() => Ok(None),
+            result                => result.map(Some),
+        }
+    }
+
     pub fn init<T: Persistent>(&self, data: &T) -> Result<(), Error> {
         let path  = &self.root.join(data.filename());
         let bytes = to_string(data).context(path)?;
@@ -41,6 +59,13 @@ impl<S: System> Store<S> {
         S::sync(path, bytes.as_bytes()).context(path)
     }
 
+    pub fn save<T: Persistent>(&self, data: &T) -> Result<(), Error> {
+        match self.sync(data) {
+            Err(e) if e.missing() => self.init(data),
+            result                => result,
+        }
+    }
+
     fn load<T: DeserializeOwned>(&self, path: &Path) -> Result<T, Error> {
         let path  = &self.root.join(path);
         let input = path.clone().into();
@@ -59,6 +84,12 @@ impl Persistent for Keychain {
     }
 }
 
+impl Persistent for Contacts {
+    fn filename(&self) -> PathBuf {
+        contacts()
+    }
+}
+
 impl Persistent for Keystore {
     fn filename(&self) -> PathBuf {
         keystore()
@@ -69,6 +100,14 @@ fn keystore() -> PathBuf {
     Path::new("signet").with_extension("yml")
 }
 
+fn contacts() -> PathBuf {
+    Path::new("contacts").with_extension("yml")
+}
+
+fn policy() -> PathBuf {
+    Path::new("policy").with_extension("yml")
+}
+
 fn keychain(name: &str) -> PathBuf {
     Path::new("chains").join(name).with_extension("yml")
 }
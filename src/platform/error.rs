@@ -2,14 +2,14 @@ use std::fmt::{Display, Formatter, Result};
 
 #[derive(Debug)]
 pub enum Error {
-    System(String),
+    Keyring(keyring::Error),
 }
 
 impl std::error::Error for Error {}
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        let Self::System(error) = self;
+        let Self::Keyring(error) = self;
         write!(f, "{}", error)
     }
 }
@@ -2,6 +2,6 @@ pub use error::Error;
 pub use system::signet;
 
 pub mod secret;
+pub mod system;
 
 mod error;
-mod system;
@@ -14,8 +14,14 @@ pub fn lookup(name: &str) -> Result<Zeroizing<String>, Error> {
     Ok(Zeroizing::new(value))
 }
 
+pub fn delete(name: &str) -> Result<(), Error> {
+    let entry = Entry::new("signet", name);
+    entry.delete_password()?;
+    Ok(())
+}
+
 impl From<keyring::Error> for Error {
     fn from(error: keyring::Error) -> Self {
-        Self::System(error.to_string())
+        Self::Keyring(error)
     }
 }
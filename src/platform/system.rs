@@ -50,6 +50,10 @@ impl System for LocalSystem {
         fs::create_dir_all(path)
     }
 
+    fn list(path: &Path) -> Result<Vec<PathBuf>> {
+        fs::read_dir(path)?.map(|entry| Ok(entry?.path())).collect()
+    }
+
     fn prompt(prompt: &str) -> Result<String> {
         prompt_password(prompt)
     }
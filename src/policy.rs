@@ -0,0 +1,311 @@
+use std::fmt;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use ssh_key::{Fingerprint, HashAlg, PublicKey};
+use crate::signers::matches;
+use crate::sshsig::Sk;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Policy {
+    version: u64,
+    #[serde(default)]
+    default: Fallback,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    groups:  IndexMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rules:   Vec<Rule>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Fallback {
+    #[default]
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Rule {
+    pub name:         String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace:    Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path:         Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group:        Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub principals:   Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys:         Vec<KeyId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security_key: Option<Assurance>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyId(String);
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Assurance {
+    Presence,
+    Verification,
+}
+
+#[derive(Clone, Debug)]
+pub struct Signature<'a> {
+    pub key:        &'a PublicKey,
+    pub principals: &'a [String],
+    pub namespace:  &'a str,
+    pub path:       Option<&'a str>,
+    pub sk:         Option<Sk>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Verdict {
+    Pass(String),
+    Fail(String),
+}
+
+#[derive(Debug)]
+pub enum Error {
+    UnknownGroup(String),
+}
+
+impl Policy {
+    pub fn evaluate<'a>(&'a self, sig: &Signature) -> Result<Vec<(&'a str, Verdict)>, Error> {
+        let mut verdicts = Vec::new();
+
+        for rule in self.rules.iter().filter(|rule| rule.applies(sig)) {
+            let members = match &rule.group {
+                Some(group) => self.group(group)?,
+                None        => &[],
+            };
+            verdicts.push((rule.name.as_str(), rule.check(sig, members)));
+        }
+
+        if verdicts.is_empty() && self.default == Fallback::Deny {
+            verdicts.push(("default", Verdict::Fail("no rule applies".to_owned())));
+        }
+
+        Ok(verdicts)
+    }
+
+    pub fn paths(&self, namespace: &str) -> bool {
+        self.rules.iter().any(|rule| {
+            let applies = rule.namespace.as_ref().is_none_or(|pattern| {
+                matches(namespace, pattern)
+            });
+            applies && rule.path.is_some()
+        })
+    }
+
+    pub fn group(&self, name: &str) -> Result<&[String], Error> {
+        match self.groups.get(name) {
+            Some(members) => Ok(members),
+            None          => Err(Error::UnknownGroup(name.to_owned())),
+        }
+    }
+}
+
+impl Rule {
+    fn applies(&self, sig: &Signature) -> bool {
+        let namespace = self.namespace.as_ref().is_none_or(|pattern| {
+            matches(sig.namespace, pattern)
+        });
+        let path = self.path.as_ref().is_none_or(|pattern| {
+            sig.path.is_some_and(|path| matches(path, pattern))
+        });
+        namespace && path
+    }
+
+
+    fn check(&self, sig: &Signature, members: &[String]) -> Verdict {
+        let fp = sig.key.fingerprint(HashAlg::Sha256);
+        let id = hex::encode(fp);
+
+        if let Some(assurance) = self.security_key {
+            if let Err(reason) = assurance.check(sig.sk) {
+                return Verdict::Fail(reason);
+            }
+        }
+
+        if self.group.is_none() && self.principals.is_empty() && self.keys.is_empty() {
+            return match self.security_key {
+                Some(assurance) => Verdict::Pass(format!("signed with {assurance}")),
+                None            => Verdict::Pass("any trusted signer".to_owned()),
+            };
+        }
+
+        if self.keys.iter().any(|key| key.0 == fp.to_string() || key.0 == id) {
+            return Verdict::Pass(format!("signed by key {fp}"));
+        }
+
+        let member = |principal: &String| {
+            members.iter().any(|pattern| matches(principal, pattern))
+        };
+
+        if let Some(principal) = sig.principals.iter().find(|p| member(p)) {
+            let group = self.group.as_deref().unwrap_or_default();
+            return Verdict::Pass(format!("signed by {principal} in group {group}"));
+        }
+
+        let listed = |principal: &String| {
+            self.principals.iter().any(|pattern| matches(principal, pattern))
+        };
+
+        if let Some(principal) = sig.principals.iter().find(|p| listed(p)) {
+            return Verdict::Pass(format!("signed by {principal}"));
+        }
+
+        let mut required = Vec::new();
+        if let Some(group) = &self.group {
+            required.push(format!("a member of group {group}"));
+        }
+        if !self.principals.is_empty() {
+            required.push(format!("one of {}", self.principals.join(",")));
+        }
+        if !self.keys.is_empty() {
+            let keys = self.keys.iter().map(|key| key.0.as_str()).collect::<Vec<_>>();
+            required.push(format!("key {}", keys.join(",")));
+        }
+
+        let signer = match sig.principals.is_empty() {
+            true  => fp.to_string(),
+            false => sig.principals.join(","),
+        };
+
+        Verdict::Fail(format!("requires {}, signed by {signer}", required.join(" or ")))
+    }
+}
+
+impl TryFrom<String> for KeyId {
+    type Error = String;
+
+    fn try_from(key: String) -> Result<Self, Self::Error> {
+        if key.len() == 64 && key.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Ok(Self(key.to_ascii_lowercase()));
+        }
+
+        match key.parse::<Fingerprint>() {
+            Ok(fp) if fp.algorithm() == HashAlg::Sha256 => Ok(Self(key)),
+            _ => Err(format!("invalid key '{key}', expected a SHA256: fingerprint or 64 digit hex id")),
+        }
+    }
+}
+
+impl From<KeyId> for String {
+    fn from(key: KeyId) -> Self {
+        key.0
+    }
+}
+
+impl Assurance {
+    fn check(self, sk: Option<Sk>) -> Result<(), String> {
+        let Some(sk) = sk else {
+            return Err("requires a security key signature".to_owned());
+        };
+
+        let satisfied = match self {
+            Self::Presence     => sk.presence(),
+            Self::Verification => sk.presence() && sk.verification(),
+        };
+
+        match satisfied {
+            true  => Ok(()),
+            false => Err(format!("requires {self}")),
+        }
+    }
+}
+
+impl Verdict {
+    pub fn passed(&self) -> bool {
+        matches!(self, Self::Pass(_))
+    }
+
+    pub fn reason(&self) -> &str {
+        match self {
+            Self::Pass(reason) => reason,
+            Self::Fail(reason) => reason,
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Pass(reason) => write!(f, "passed, {reason}"),
+            Self::Fail(reason) => write!(f, "failed, {reason}"),
+        }
+    }
+}
+
+impl fmt::Display for Assurance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Presence     => write!(f, "user presence on a security key"),
+            Self::Verification => write!(f, "user verification on a security key"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownGroup(group) => write!(f, "unknown policy group '{group}'"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sshsig::Sk;
+    use super::{Assurance, Policy};
+
+    #[test]
+    fn security_key_assurance() {
+        let none     = Sk { flags: 0x00, counter: 1 };
+        let touch    = Sk { flags: 0x01, counter: 1 };
+        let pin      = Sk { flags: 0x04, counter: 1 };
+        let verified = Sk { flags: 0x05, counter: 1 };
+
+        assert!(Assurance::Presence.check(None).is_err());
+        assert!(Assurance::Presence.check(Some(none)).is_err());
+        assert!(Assurance::Presence.check(Some(pin)).is_err());
+        assert!(Assurance::Presence.check(Some(touch)).is_ok());
+        assert!(Assurance::Presence.check(Some(verified)).is_ok());
+
+        assert!(Assurance::Verification.check(None).is_err());
+        assert!(Assurance::Verification.check(Some(touch)).is_err());
+        assert!(Assurance::Verification.check(Some(pin)).is_err());
+        assert!(Assurance::Verification.check(Some(verified)).is_ok());
+    }
+
+    #[test]
+    fn path_rules() {
+        let policy = serde_yaml::from_str::<Policy>("
+version: 1
+rules:
+  - name:      releases
+    namespace: release
+  - name:      firmware
+    namespace: file
+    path:      firmware/*
+").unwrap();
+
+        assert!(policy.paths("file"));
+        assert!(!policy.paths("release"));
+
+        let policy = serde_yaml::from_str::<Policy>("
+version: 1
+rules:
+  - name: firmware
+    path: firmware/*
+").unwrap();
+
+        assert!(policy.paths("file"));
+        assert!(policy.paths("release"));
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    Magic,
+    Version(u32),
+    Section(u8),
+    Invalid(ssh_key::Error),
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Magic          => write!(f, "not a KRL"),
+            Self::Version(n)     => write!(f, "unsupported KRL format version {n}"),
+            Self::Section(n)     => write!(f, "unsupported KRL section {n}"),
+            Self::Invalid(error) => write!(f, "invalid KRL: {error}"),
+        }
+    }
+}
+
+impl From<ssh_key::Error> for Error {
+    fn from(error: ssh_key::Error) -> Self {
+        Self::Invalid(error)
+    }
+}
+
+impl From<ssh_encoding::Error> for Error {
+    fn from(error: ssh_encoding::Error) -> Self {
+        Self::Invalid(error.into())
+    }
+}
@@ -0,0 +1,238 @@
+use sha1::{Digest, Sha1};
+use ssh_encoding::{Decode, Encode, Reader};
+use ssh_key::{HashAlg, PublicKey};
+use ssh_key::public::KeyData;
+use super::Error;
+
+const MAGIC:   &[u8; 8] = b"SSHKRL\n\0";
+const VERSION: u32      = 1;
+
+const SECTION_CERTIFICATES:       u8 = 1;
+const SECTION_EXPLICIT_KEY:       u8 = 2;
+const SECTION_FINGERPRINT_SHA1:   u8 = 3;
+const SECTION_SIGNATURE:          u8 = 4;
+const SECTION_FINGERPRINT_SHA256: u8 = 5;
+
+#[derive(Clone, Debug, Default)]
+pub struct Krl {
+    pub version:   u64,
+    pub generated: u64,
+    pub comment:   String,
+    pub keys:      Vec<KeyData>,
+    pub sha1:      Vec<Vec<u8>>,
+    pub sha256:    Vec<Vec<u8>>,
+}
+
+impl Krl {
+    pub fn is_krl(data: &[u8]) -> bool {
+        data.starts_with(MAGIC)
+    }
+
+    pub fn decode(mut data: &[u8]) -> Result<Self, Error> {
+        let reader = &mut data;
+
+        let mut magic = [0u8; MAGIC.len()];
+        reader.read(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::Magic);
+        }
+
+        let format = u32::decode(reader)?;
+        if format != VERSION {
+            return Err(Error::Version(format));
+        }
+
+        let mut krl = Self {
+            version:   u64::decode(reader)?,
+            generated: u64::decode(reader)?,
+            ..Default::default()
+        };
+
+        let _flags    = u64::decode(reader)?;
+        let _reserved = Vec::<u8>::decode(reader)?;
+        krl.comment   = String::decode(reader)?;
+
+        while !reader.is_finished() {
+            let kind = u8::decode(reader)?;
+            let data = Vec::<u8>::decode(reader)?;
+            let data = &mut &data[..];
+
+            match kind {
+                SECTION_EXPLICIT_KEY       => krl.keys.extend(blobs(data, key)?),
+                SECTION_FINGERPRINT_SHA1   => krl.sha1.extend(blobs(data, Ok)?),
+                SECTION_FINGERPRINT_SHA256 => krl.sha256.extend(blobs(data, Ok)?),
+                SECTION_CERTIFICATES       => (),
+                SECTION_SIGNATURE          => (),
+                kind                       => return Err(Error::Section(kind)),
+            }
+        }
+
+        Ok(krl)
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, Error> {
+        let mut vec = Vec::new();
+        vec.extend_from_slice(MAGIC);
+        VERSION.encode(&mut vec)?;
+        self.version.encode(&mut vec)?;
+        self.generated.encode(&mut vec)?;
+        0u64.encode(&mut vec)?;
+        "".encode(&mut vec)?;
+        self.comment.encode(&mut vec)?;
+
+        let keys = self.keys.iter().map(|key| {
+            PublicKey::from(key.clone()).to_bytes()
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        let sections = [
+            (SECTION_EXPLICIT_KEY,       &keys),
+            (SECTION_FINGERPRINT_SHA1,   &self.sha1),
+            (SECTION_FINGERPRINT_SHA256, &self.sha256),
+        ];
+
+        for (kind, blobs) in sections.into_iter().filter(|(_, blobs)| !blobs.is_empty()) {
+            let mut data = Vec::new();
+            for blob in blobs {
+                blob.encode(&mut data)?;
+            }
+            kind.encode(&mut vec)?;
+            data.encode(&mut vec)?;
+        }
+
+        Ok(vec)
+    }
+
+    pub fn contains(&self, key: &KeyData) -> bool {
+        let revoked = || -> Result<bool, Error> {
+            let blob   = PublicKey::from(key.clone()).to_bytes()?;
+            let sha1   = Sha1::digest(&blob).to_vec();
+            let sha256 = key.fingerprint(HashAlg::Sha256).as_bytes().to_vec();
+            Ok(self.sha1.contains(&sha1) || self.sha256.contains(&sha256))
+        };
+        self.keys.contains(key) || revoked().unwrap_or(true)
+    }
+}
+
+fn blobs<T>(reader: &mut &[u8], f: impl Fn(Vec<u8>) -> Result<T, Error>) -> Result<Vec<T>, Error> {
+    let mut vec = Vec::new();
+    while !reader.is_finished() {
+        vec.push(f(Vec::<u8>::decode(reader)?)?);
+    }
+    Ok(vec)
+}
+
+fn key(blob: Vec<u8>) -> Result<KeyData, Error> {
+    Ok(PublicKey::from_bytes(&blob)?.key_data().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+    use ssh_key::PublicKey;
+    use tempfile::TempDir;
+    use super::super::Error;
+    use super::Krl;
+
+    fn keygen(dir: &Path, args: &[&str]) -> bool {
+        let output = Command::new("ssh-keygen").args(args).current_dir(dir).output().unwrap();
+        output.status.success()
+    }
+
+    fn key(dir: &Path, name: &str) -> PublicKey {
+        assert!(keygen(dir, &["-q", "-t", "ed25519", "-N", "", "-C", name, "-f", name]));
+        PublicKey::from_openssh(&fs::read_to_string(dir.join(format!("{name}.pub"))).unwrap()).unwrap()
+    }
+
+    fn spec(dir: &Path, lines: &[String]) -> Krl {
+        fs::write(dir.join("spec"), lines.join("\n")).unwrap();
+        assert!(keygen(dir, &["-k", "-f", "krl", "-s", "ca.pub", "-z", "7", "spec"]));
+        Krl::decode(&fs::read(dir.join("krl")).unwrap()).unwrap()
+    }
+
+    fn revoked(dir: &Path, krl: &Krl, name: &str) -> bool {
+        fs::write(dir.join("encoded"), krl.encode().unwrap()).unwrap();
+        !keygen(dir, &["-Q", "-f", "encoded", &format!("{name}.pub")])
+    }
+
+    #[test]
+    fn key_sections() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path();
+
+        let [_, explicit, sha1, sha256, hash, other] = ["ca", "explicit", "sha1", "sha256", "hash", "other"].map(|name| {
+            key(dir, name)
+        });
+
+        let krl = spec(dir, &[
+            format!("key: {}", explicit.to_openssh().unwrap()),
+            format!("sha1: {}", sha1.to_openssh().unwrap()),
+            format!("sha256: {}", sha256.to_openssh().unwrap()),
+            format!("hash: {}", hash.fingerprint(ssh_key::HashAlg::Sha256)),
+        ]);
+
+        assert_eq!(krl.version, 7);
+        assert_eq!((krl.keys.len(), krl.sha1.len(), krl.sha256.len()), (1, 1, 2));
+
+        for key in [&explicit, &sha1, &sha256, &hash] {
+            assert!(krl.contains(key.key_data()), "{}", key.comment());
+        }
+        assert!(!krl.contains(other.key_data()));
+
+        let encoded = Krl::decode(&krl.encode().unwrap()).unwrap();
+        assert_eq!(encoded.keys, krl.keys);
+        assert_eq!(encoded.sha1, krl.sha1);
+        assert_eq!(encoded.sha256, krl.sha256);
+        assert_eq!(encoded.version, krl.version);
+
+        for name in ["explicit", "sha1", "sha256", "hash"] {
+            assert!(revoked(dir, &krl, name), "{name}");
+        }
+        assert!(!revoked(dir, &krl, "other"));
+    }
+
+    #[test]
+    fn certificate_sections() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path();
+
+        let [_, key, other] = ["ca", "key", "other"].map(|name| self::key(dir, name));
+
+        let krl = spec(dir, &[
+            "serial: 1".to_owned(),
+            "serial: 10-20".to_owned(),
+            "serial: 30".to_owned(),
+            "serial: 33".to_owned(),
+            "serial: 40".to_owned(),
+            "id: alice@example.com".to_owned(),
+            format!("key: {}", key.to_openssh().unwrap()),
+        ]);
+
+        assert_eq!(krl.keys, [key.key_data().clone()]);
+        assert!(krl.contains(key.key_data()));
+        assert!(!krl.contains(other.key_data()));
+    }
+
+    #[test]
+    fn invalid() {
+        let krl = Krl::default().encode().unwrap();
+        assert!(Krl::decode(&krl).is_ok());
+
+        let mut magic = krl.clone();
+        magic[0] = b'X';
+        assert!(matches!(Krl::decode(&magic), Err(Error::Magic)));
+
+        let mut version = krl.clone();
+        version[11] = 2;
+        assert!(matches!(Krl::decode(&version), Err(Error::Version(2))));
+
+        let mut section = krl.clone();
+        section.extend([9, 0, 0, 0, 0]);
+        assert!(matches!(Krl::decode(&section), Err(Error::Section(9))));
+
+        let mut truncated = krl;
+        truncated.extend([2, 0, 0, 0, 8, 0]);
+        assert!(matches!(Krl::decode(&truncated), Err(Error::Invalid(_))));
+    }
+}
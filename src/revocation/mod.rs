@@ -0,0 +1,9 @@
+pub use revocation::Revocations;
+
+pub use krl::Krl;
+
+pub use error::Error;
+
+mod error;
+mod krl;
+mod revocation;
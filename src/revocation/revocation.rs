@@ -0,0 +1,33 @@
+use ssh_key::PublicKey;
+use ssh_key::public::KeyData;
+use super::{Error, Krl};
+
+#[derive(Clone, Debug)]
+pub enum Revocations {
+    Krl(Krl),
+    Keys(Vec<KeyData>),
+}
+
+impl Revocations {
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        if Krl::is_krl(data) {
+            return Ok(Self::Krl(Krl::decode(data)?));
+        }
+
+        let data = String::from_utf8_lossy(data);
+        let keys = data.lines().filter_map(|line| {
+            let line = line.trim_start_matches([' ', '\t']);
+            let key  = PublicKey::from_openssh(line).ok()?;
+            Some(key.key_data().clone())
+        }).collect();
+
+        Ok(Self::Keys(keys))
+    }
+
+    pub fn contains(&self, key: &KeyData) -> bool {
+        match self {
+            Self::Krl(krl)   => krl.contains(key),
+            Self::Keys(keys) => keys.contains(key),
+        }
+    }
+}
@@ -2,7 +2,7 @@ use rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroizing;
 use crate::platform::Error;
-use crate::platform::secret::{create, lookup};
+use crate::platform::secret::{create, delete, lookup};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Secret {
@@ -23,4 +23,8 @@ impl Secret {
     pub fn lookup(&self) -> Result<Zeroizing<String>, Error> {
         lookup(&self.name)
     }
+
+    pub fn delete(&self) -> Result<(), Error> {
+        delete(&self.name)
+    }
 }
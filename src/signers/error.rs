@@ -0,0 +1,62 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+use crate::timestamp::format;
+
+#[derive(Debug)]
+pub struct Error {
+    cause: Cause,
+    path:  PathBuf,
+    line:  usize,
+}
+
+#[derive(Debug)]
+pub enum Cause {
+    InvalidLine,
+    InvalidOptions,
+    InvalidKey,
+    MissingKey,
+    BadOptions(&'static str),
+    Namespace(String),
+    NotYetValid(OffsetDateTime, OffsetDateTime),
+    Expired(OffsetDateTime, OffsetDateTime),
+}
+
+impl Error {
+    pub fn new(cause: Cause, path: &Path, line: usize) -> Self {
+        let path = path.to_owned();
+        Self { cause, path, line }
+    }
+
+    pub fn rejected(&self) -> bool {
+        matches!(self.cause, Cause::Namespace(_) | Cause::NotYetValid(..) | Cause::Expired(..))
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = self.path.display();
+        let line = self.line;
+        write!(f, "{path}:{line}: ")?;
+        match &self.cause {
+            Cause::InvalidLine       => write!(f, "invalid line"),
+            Cause::InvalidOptions    => write!(f, "invalid options"),
+            Cause::InvalidKey        => write!(f, "invalid key"),
+            Cause::MissingKey        => write!(f, "missing key"),
+            Cause::BadOptions(error) => write!(f, "bad options: {error}"),
+            Cause::Namespace(ns)     => {
+                write!(f, "key is not permitted for use in signature namespace \"{ns}\"")
+            },
+            Cause::NotYetValid(time, after) => {
+                let (time, after) = (format(*time), format(*after));
+                write!(f, "key is not yet valid: verify time {time} < valid-after {after}")
+            },
+            Cause::Expired(time, before) => {
+                let (time, before) = (format(*time), format(*before));
+                write!(f, "key has expired: verify time {time} > valid-before {before}")
+            },
+        }
+    }
+}
@@ -0,0 +1,15 @@
+pub use signers::AllowedSigners;
+pub use signers::Query;
+pub use signers::Signer;
+
+pub use options::Options;
+
+pub use pattern::matches;
+
+pub use error::Cause;
+pub use error::Error;
+
+mod error;
+mod options;
+mod pattern;
+mod signers;
@@ -0,0 +1,145 @@
+use std::str::FromStr;
+use time::OffsetDateTime;
+use crate::timestamp;
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub authority:  bool,
+    pub namespaces: Option<String>,
+    pub after:      Option<OffsetDateTime>,
+    pub before:     Option<OffsetDateTime>,
+}
+
+impl FromStr for Options {
+    type Err = &'static str;
+
+    fn from_str(mut opts: &str) -> Result<Self, Self::Err> {
+        let mut options = Self::default();
+
+        while !opts.is_empty() {
+            if let Some(rest) = flag(opts, "cert-authority") {
+                options.authority = true;
+                opts = rest;
+            } else if let Some(rest) = named(opts, "namespaces") {
+                if options.namespaces.is_some() {
+                    return Err("multiple \"namespaces\" clauses");
+                }
+                let (value, rest) = dequote(rest)?;
+                options.namespaces = Some(value);
+                opts = rest;
+            } else if let Some(rest) = named(opts, "valid-after") {
+                if options.after.is_some() {
+                    return Err("multiple \"valid-after\" clauses");
+                }
+                let (value, rest) = dequote(rest)?;
+                let time = timestamp::parse(&value);
+                options.after = Some(time.ok_or("invalid \"valid-after\" time")?);
+                opts = rest;
+            } else if let Some(rest) = named(opts, "valid-before") {
+                if options.before.is_some() {
+                    return Err("multiple \"valid-before\" clauses");
+                }
+                let (value, rest) = dequote(rest)?;
+                let time = timestamp::parse(&value);
+                options.before = Some(time.ok_or("invalid \"valid-before\" time")?);
+                opts = rest;
+            }
+
+            opts = match opts.strip_prefix(',') {
+                Some("")                => return Err("unexpected end-of-options"),
+                Some(rest)              => rest,
+                None if opts.is_empty() => break,
+                None                    => return Err("unknown key option"),
+            };
+        }
+
+        if let (Some(after), Some(before)) = (options.after, options.before) {
+            if before <= after {
+                return Err("\"valid-before\" time is before \"valid-after\"");
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn flag<'a>(opts: &'a str, name: &str) -> Option<&'a str> {
+    let (head, tail) = (opts.get(..name.len())?, opts.get(name.len()..)?);
+    head.eq_ignore_ascii_case(name).then_some(tail)
+}
+
+fn named<'a>(opts: &'a str, name: &str) -> Option<&'a str> {
+    flag(opts, name)?.strip_prefix('=')
+}
+
+fn dequote(opts: &str) -> Result<(String, &str), &'static str> {
+    let opts = opts.strip_prefix('"').ok_or("missing start quote")?;
+
+    let mut value = String::new();
+    let mut chars = opts.char_indices();
+
+    while let Some((index, char)) = chars.next() {
+        match char {
+            '"'  => return Ok((value, &opts[index + 1..])),
+            '\\' if opts[index + 1..].starts_with('"') => {
+                value.push('"');
+                chars.next();
+            },
+            char => value.push(char),
+        }
+    }
+
+    Err("missing end quote")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timestamp::parse;
+    use super::Options;
+
+    #[test]
+    fn quoted() {
+        let options = r#"cert-authority,namespaces="git,file",valid-after="20200101Z""#;
+        let options = options.parse::<Options>().unwrap();
+        assert!(options.authority);
+        assert_eq!(options.namespaces.as_deref(), Some("git,file"));
+        assert_eq!(options.after, parse("20200101Z"));
+        assert_eq!(options.before, None);
+
+        let options = r#"NAMESPACES="a\"b""#.parse::<Options>().unwrap();
+        assert_eq!(options.namespaces.as_deref(), Some("a\"b"));
+    }
+
+    #[test]
+    fn time_window() {
+        let options = r#"valid-after="20200101Z",valid-before="20210101000000Z""#;
+        let options = options.parse::<Options>().unwrap();
+        assert_eq!(options.after, parse("20200101Z"));
+        assert_eq!(options.before, parse("20210101Z"));
+
+        let invalid = [
+            r#"valid-after="20210101Z",valid-before="20200101Z""#,
+            r#"valid-after="20200101Z",valid-before="20200101Z""#,
+            r#"valid-after="2020""#,
+            r#"valid-before="20201301Z""#,
+        ];
+        for options in invalid {
+            assert!(options.parse::<Options>().is_err(), "{options}");
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let invalid = [
+            ("namespaces=git",                     "missing start quote"),
+            (r#"namespaces="git"#,                 "missing end quote"),
+            (r#"namespaces="a",namespaces="b""#,   "multiple \"namespaces\" clauses"),
+            ("cert-authority,",                    "unexpected end-of-options"),
+            ("restrict",                           "unknown key option"),
+            ("cert-authorityx",                    "unknown key option"),
+        ];
+        for (options, error) in invalid {
+            assert_eq!(options.parse::<Options>().unwrap_err(), error, "{options}");
+        }
+    }
+}
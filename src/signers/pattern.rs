@@ -0,0 +1,50 @@
+pub fn matches(str: &str, list: &str) -> bool {
+    let mut matched = false;
+    for pattern in list.split(',') {
+        match pattern.strip_prefix('!') {
+            Some(negated) => if glob(str, negated) {
+                return false;
+            },
+            None          => matched |= glob(str, pattern),
+        }
+    }
+    matched
+}
+
+fn glob(str: &str, pattern: &str) -> bool {
+    fn glob(str: &[u8], pattern: &[u8]) -> bool {
+        match (pattern.split_first(), str.split_first()) {
+            (None, _)                          => str.is_empty(),
+            (Some((b'*', rest)), _)            => (0..=str.len()).any(|n| glob(&str[n..], rest)),
+            (Some((b'?', rest)), Some((_, s))) => glob(s, rest),
+            (Some((p, rest)), Some((c, s)))    => p == c && glob(s, rest),
+            (Some(_), None)                    => false,
+        }
+    }
+    glob(str.as_bytes(), pattern.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn patterns() {
+        assert!(matches("alice@example.com", "alice@example.com"));
+        assert!(matches("alice@example.com", "*@example.com"));
+        assert!(matches("alice@example.com", "bob@example.com,alice@*"));
+        assert!(matches("git", "?i?"));
+        assert!(matches("", "*"));
+        assert!(!matches("alice@example.com", "alice"));
+        assert!(!matches("alice@example.com", "?@example.com"));
+        assert!(!matches("alice@example.com", ""));
+    }
+
+    #[test]
+    fn negated() {
+        assert!(matches("alice@example.com", "*@example.com,!bob@example.com"));
+        assert!(!matches("bob@example.com", "*@example.com,!bob@example.com"));
+        assert!(!matches("bob@example.com", "!bob@example.com,*"));
+        assert!(!matches("alice@example.com", "!bob@example.com"));
+    }
+}
@@ -0,0 +1,255 @@
+use std::path::{Path, PathBuf};
+use ssh_key::PublicKey;
+use ssh_key::public::KeyData;
+use time::OffsetDateTime;
+use super::{matches, Cause, Error, Options};
+
+#[derive(Debug)]
+pub struct AllowedSigners {
+    path:  PathBuf,
+    lines: Vec<(usize, String)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Signer {
+    pub principals: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Query<'a> {
+    pub key:       &'a KeyData,
+    pub principal: Option<&'a str>,
+    pub namespace: Option<&'a str>,
+    pub time:      OffsetDateTime,
+}
+
+impl AllowedSigners {
+    pub fn new(path: &Path, data: &[u8]) -> Self {
+        let data  = String::from_utf8_lossy(data);
+        let lines = data.lines().enumerate().filter_map(|(index, line)| {
+            let line = line.trim_start_matches([' ', '\t']);
+            let skip = line.is_empty() || line.starts_with('#');
+            (!skip).then(|| (index + 1, line.to_owned()))
+        }).collect();
+        let path = path.to_owned();
+        Self { path, lines }
+    }
+
+    pub fn find<'a>(&'a self, query: &'a Query) -> impl Iterator<Item = Result<Signer, Error>> + 'a {
+        self.lines.iter().filter_map(|(number, line)| {
+            self.check(line, query).map_err(|cause| {
+                Error::new(cause, &self.path, *number)
+            }).transpose()
+        })
+    }
+
+    pub fn principals(&self, principal: &str) -> Result<Vec<Signer>, Error> {
+        let mut signers = Vec::new();
+        for (number, line) in &self.lines {
+            match parse(line, Some(principal)) {
+                Ok(Some((principals, ..))) => signers.push(Signer { principals }),
+                Ok(None)                   => (),
+                Err(cause)                 => return Err(Error::new(cause, &self.path, *number)),
+            }
+        }
+        Ok(signers)
+    }
+
+    fn check(&self, line: &str, query: &Query) -> Result<Option<Signer>, Cause> {
+        let (principals, options, key) = match parse(line, query.principal)? {
+            Some(entry) => entry,
+            None        => return Ok(None),
+        };
+
+        if options.authority || key.key_data() != query.key {
+            return Ok(None);
+        }
+
+        if let (Some(patterns), Some(ns)) = (&options.namespaces, query.namespace) {
+            if !matches(ns, patterns) {
+                return Err(Cause::Namespace(ns.to_owned()));
+            }
+        }
+
+        let time = query.time;
+
+        if let Some(after) = options.after.filter(|after| time < *after) {
+            return Err(Cause::NotYetValid(time, after));
+        }
+
+        if let Some(before) = options.before.filter(|before| time > *before) {
+            return Err(Cause::Expired(time, before));
+        }
+
+        Ok(Some(Signer { principals }))
+    }
+}
+
+impl Signer {
+    pub fn principals(&self) -> impl Iterator<Item = &str> {
+        self.principals.split(',')
+    }
+}
+
+fn parse(line: &str, principal: Option<&str>) -> Result<Option<(String, Options, PublicKey)>, Cause> {
+    let (principals, rest) = token(line).ok_or(Cause::InvalidLine)?;
+
+    if let Some(principal) = principal {
+        if !matches(principal, &principals) {
+            return Ok(None);
+        }
+    }
+
+    let (options, key) = match PublicKey::from_openssh(rest) {
+        Ok(key) => (Options::default(), key),
+        Err(_)  => {
+            let (options, rest) = options(rest).ok_or(Cause::InvalidOptions)?;
+            if rest.is_empty() {
+                return Err(Cause::MissingKey);
+            }
+            let key = PublicKey::from_openssh(rest).map_err(|_| Cause::InvalidKey)?;
+            (options.parse().map_err(Cause::BadOptions)?, key)
+        },
+    };
+
+    Ok(Some((principals, options, key)))
+}
+
+fn token(line: &str) -> Option<(String, &str)> {
+    let end = line.find([' ', '\t', '"']).unwrap_or(line.len());
+    let (mut token, mut rest) = (line[..end].to_owned(), &line[end..]);
+
+    if let Some(quoted) = rest.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        token.push_str(&quoted[..end]);
+        rest = &quoted[end + 1..];
+    }
+
+    let rest = rest.trim_start_matches([' ', '\t']);
+    let rest = rest.trim_end_matches(['\r', '\n']);
+
+    (!rest.is_empty()).then_some((token, rest))
+}
+
+fn options(line: &str) -> Option<(&str, &str)> {
+    let mut quoted = false;
+    let mut chars  = line.char_indices();
+
+    while let Some((index, char)) = chars.next() {
+        match char {
+            '\\' if line[index + 1..].starts_with('"') => { chars.next(); },
+            '"'                                        => quoted = !quoted,
+            ' ' | '\t' if !quoted                      => {
+                let rest = line[index..].trim_start_matches([' ', '\t']);
+                return Some((&line[..index], rest));
+            },
+            _                                          => (),
+        }
+    }
+
+    (!quoted).then_some((line, ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use rand_core::OsRng;
+    use ssh_key::{Algorithm, PrivateKey, PublicKey};
+    use crate::timestamp::parse;
+    use super::{AllowedSigners, Query};
+
+    fn key() -> PublicKey {
+        PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap().public_key().clone()
+    }
+
+    fn query<'a>(key: &'a PublicKey, principal: Option<&'a str>, namespace: Option<&'a str>, time: &str) -> Query<'a> {
+        Query {
+            key:       key.key_data(),
+            principal: principal,
+            namespace: namespace,
+            time:      parse(time).unwrap(),
+        }
+    }
+
+    #[test]
+    fn find() {
+        let [a, b, c, d] = [(); 4].map(|_| key());
+        let [a, b, c, d] = [&a, &b, &c, &d];
+        let [ka, kb, kc, kd] = [a, b, c, d].map(|key| key.to_openssh().unwrap());
+
+        let data = format!("
+# comment
+
+  alice@example.com {ka}
+\"bob@example.com,carol@example.com\" namespaces=\"git,release-*\" {kb}
+dave@example.com cert-authority {kc}
+erin@example.com valid-after=\"20200101Z\",valid-before=\"20300101Z\" {kd}
+");
+        let signers = AllowedSigners::new(Path::new("allowed_signers"), data.as_bytes());
+        let find    = |query: Query| signers.find(&query).collect::<Vec<_>>();
+
+        let found = find(query(a, Some("alice@example.com"), Some("file"), "20250101Z"));
+        assert_eq!(found[0].as_ref().unwrap().principals, "alice@example.com");
+        assert!(find(query(a, Some("bob@example.com"), None, "20250101Z")).is_empty());
+
+        let found = find(query(b, None, Some("release-1"), "20250101Z"));
+        let found = found[0].as_ref().unwrap();
+        assert_eq!(found.principals().collect::<Vec<_>>(), ["bob@example.com", "carol@example.com"]);
+
+        let found = find(query(b, Some("carol@example.com"), Some("file"), "20250101Z"));
+        let error = found[0].as_ref().unwrap_err();
+        assert!(error.rejected());
+        assert_eq!(error.to_string(), "allowed_signers:5: key is not permitted for use in signature namespace \"file\"");
+
+        assert!(find(query(c, None, None, "20250101Z")).is_empty());
+
+        assert!(find(query(d, None, None, "20250101Z"))[0].is_ok());
+        for time in ["20191231Z", "20300102Z"] {
+            let found = find(query(d, None, None, time));
+            assert!(found[0].as_ref().unwrap_err().rejected(), "{time}");
+        }
+    }
+
+    #[test]
+    fn principals() {
+        let key  = key().to_openssh().unwrap();
+        let data = format!("
+*@example.com,!mallory@example.com {key}
+mallory@example.com,alice@example.com {key}
+");
+        let signers = AllowedSigners::new(Path::new("allowed_signers"), data.as_bytes());
+
+        let principals = |principal| {
+            signers.principals(principal).unwrap().into_iter().map(|signer| {
+                signer.principals
+            }).collect::<Vec<_>>()
+        };
+
+        assert_eq!(principals("alice@example.com").len(), 2);
+        assert_eq!(principals("mallory@example.com"), ["mallory@example.com,alice@example.com"]);
+        assert!(principals("alice@example.org").is_empty());
+    }
+
+    #[test]
+    fn invalid() {
+        let key     = key();
+        let openssh = key.to_openssh().unwrap();
+
+        let invalid = [
+            ("alice@example.com".to_owned(),                          "invalid line"),
+            ("alice@example.com namespaces=\"git\"".to_owned(),       "missing key"),
+            (format!("alice@example.com namespaces=\"git {openssh}"), "invalid options"),
+            ("alice@example.com ssh-ed25519 AAAA".to_owned(),         "invalid key"),
+            (format!("alice@example.com restrict {openssh}"),         "bad options: unknown key option"),
+            (format!("\"alice@example.com {openssh}"),                "invalid line"),
+        ];
+
+        for (line, error) in invalid {
+            let data    = format!("# comment\n{line}\n");
+            let signers = AllowedSigners::new(Path::new("allowed_signers"), data.as_bytes());
+            let found   = signers.find(&query(&key, None, None, "20250101Z")).collect::<Vec<_>>();
+            let message = found[0].as_ref().unwrap_err().to_string();
+            assert_eq!(message, format!("allowed_signers:2: {error}"), "{line}");
+        }
+    }
+}
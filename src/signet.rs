@@ -1,6 +1,6 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use anyhow::Error;
-use crate::{Key, Keychain, Keystore, Metadata, Password, Provider};
+use crate::{Contacts, Expiry, Key, Keychain, Keystore, Metadata, Password, Policy, Provider};
 use crate::persist::{Persistent, Store};
 use crate::system::System;
 
@@ -21,6 +21,7 @@ impl<S: System> Signet<S> {
         let keychain = Keychain::new(Metadata {
             identity: default.to_owned(),
             password: password,
+            expiry:   Expiry::default(),
         }, Provider::Local);
 
         let mut keystore = Keystore::default();
@@ -33,6 +34,10 @@ impl<S: System> Signet<S> {
         Ok(())
     }
 
+    pub fn root(&self) -> &Path {
+        self.store.root()
+    }
+
     pub fn current(&self) -> Result<Keychain, Error> {
         let keystore = self.keystore()?;
         let current  = keystore.current()?;
@@ -52,7 +57,19 @@ impl<S: System> Signet<S> {
         Ok(self.store.keychain(name)?)
     }
 
+    pub fn contacts(&self) -> Result<Contacts, Error> {
+        Ok(self.store.contacts()?)
+    }
+
+    pub fn policy(&self) -> Result<Option<Policy>, Error> {
+        Ok(self.store.policy()?)
+    }
+
     pub fn sync<T: Persistent>(&self, data: &T) -> Result<(), Error> {
         Ok(self.store.sync(data)?)
     }
+
+    pub fn save<T: Persistent>(&self, data: &T) -> Result<(), Error> {
+        Ok(self.store.save(data)?)
+    }
 }
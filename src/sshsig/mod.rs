@@ -0,0 +1,4 @@
+pub use sshsig::SshSig;
+pub use sshsig::Sk;
+
+mod sshsig;
@@ -0,0 +1,435 @@
+use sha2::{Digest, Sha256};
+use signature::Verifier;
+use ssh_encoding::{Decode, Encode, Reader, Writer};
+use ssh_encoding::pem::PemLabel;
+use ssh_key::{Algorithm, EcdsaCurve, Error, HashAlg, MPInt, Signature};
+use ssh_key::public::KeyData;
+
+const MAGIC:   &[u8] = b"SSHSIG";
+const VERSION: u32   = 1;
+#[derive(Clone, Debug)]
+pub struct SshSig {
+    public_key: KeyData,
+    namespace:  String,
+    reserved:   Vec<u8>,
+    hash_alg:   HashAlg,
+    algorithm:  Algorithm,
+    signature:  Vec<u8>,
+    sk:         Option<Sk>,
+}
+#[derive(Clone, Copy, Debug)]
+pub struct Sk {
+    pub flags:   u8,
+    pub counter: u32,
+}
+
+impl SshSig {
+    pub fn from_pem(pem: impl AsRef<[u8]>) -> Result<Self, Error> {
+        ssh_encoding::DecodePem::decode_pem(pem)
+    }
+
+    pub fn public_key(&self) -> &KeyData {
+        &self.public_key
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn sk(&self) -> Option<Sk> {
+        self.sk
+    }
+    pub fn verify(&self, ns: &str, msg: &[u8]) -> Result<(), Error> {
+        if ns != self.namespace {
+            return Err(Error::Namespace);
+        }
+
+        match (&self.public_key, self.algorithm) {
+            (KeyData::Rsa(_), Algorithm::Rsa { .. }) => (),
+            (key, algorithm) if key.algorithm() == algorithm => (),
+            _                                        => return Err(Error::Algorithm),
+        }
+
+        let data = ssh_key::SshSig::signed_data(ns, self.hash_alg, msg)?;
+        let sig  = &self.signature;
+
+        match (&self.public_key, self.sk) {
+            (KeyData::Ecdsa(key), _) => {
+                ecdsa(key.curve(), key.as_sec1_bytes(), &data, sig)
+            },
+            (KeyData::SkEcdsaSha2NistP256(key), Some(sk)) => {
+                let data = sk.message(key.application(), &data);
+                ecdsa(EcdsaCurve::NistP256, key.ec_point().as_bytes(), &data, sig)
+            },
+            (KeyData::SkEd25519(key), Some(sk)) => {
+                let data = sk.message(key.application(), &data);
+                let key  = KeyData::Ed25519(*key.public_key());
+                let sig  = Signature::new(Algorithm::Ed25519, sig.clone())?;
+                key.verify(&data, &sig).map_err(|_| Error::Crypto)
+            },
+            (key, _) => {
+                let sig = Signature::new(self.algorithm, sig.clone())?;
+                key.verify(&data, &sig).map_err(|_| Error::Crypto)
+            },
+        }
+    }
+
+    fn signature_len(&self) -> Result<usize, Error> {
+        let sk = match self.sk {
+            Some(_) => 5,
+            None    => 0,
+        };
+        Ok(self.algorithm.encoded_len()? + self.signature.encoded_len()? + sk)
+    }
+}
+
+impl Sk {
+    const PRESENCE:     u8 = 0x01;
+    const VERIFICATION: u8 = 0x04;
+    pub fn presence(&self) -> bool {
+        self.flags & Self::PRESENCE != 0
+    }
+    pub fn verification(&self) -> bool {
+        self.flags & Self::VERIFICATION != 0
+    }
+    fn message(&self, application: &str, data: &[u8]) -> Vec<u8> {
+        let mut message = Vec::new();
+        message.extend(Sha256::digest(application));
+        message.push(self.flags);
+        message.extend(self.counter.to_be_bytes());
+        message.extend(Sha256::digest(data));
+        message
+    }
+}
+
+fn ecdsa(curve: EcdsaCurve, point: &[u8], data: &[u8], sig: &[u8]) -> Result<(), Error> {
+    let blob = &mut &sig[..];
+    let r    = MPInt::decode(blob)?;
+    let s    = MPInt::decode(blob)?;
+
+    if !blob.is_finished() {
+        return Err(Error::Crypto);
+    }
+
+    match curve {
+        EcdsaCurve::NistP256 => {
+            use p256::ecdsa::{Signature, VerifyingKey};
+            let key = VerifyingKey::from_sec1_bytes(point).map_err(|_| Error::Crypto)?;
+            let sig = Signature::from_scalars(
+                p256::FieldBytes::clone_from_slice(&scalar::<32>(&r)?),
+                p256::FieldBytes::clone_from_slice(&scalar::<32>(&s)?),
+            ).map_err(|_| Error::Crypto)?;
+            key.verify(data, &sig).map_err(|_| Error::Crypto)
+        },
+        EcdsaCurve::NistP384 => {
+            use p384::ecdsa::{Signature, VerifyingKey};
+            let key = VerifyingKey::from_sec1_bytes(point).map_err(|_| Error::Crypto)?;
+            let sig = Signature::from_scalars(
+                p384::FieldBytes::clone_from_slice(&scalar::<48>(&r)?),
+                p384::FieldBytes::clone_from_slice(&scalar::<48>(&s)?),
+            ).map_err(|_| Error::Crypto)?;
+            key.verify(data, &sig).map_err(|_| Error::Crypto)
+        },
+        EcdsaCurve::NistP521 => {
+            use p521::ecdsa::{Signature, VerifyingKey};
+            use p521::ecdsa::signature::Verifier;
+            let key = VerifyingKey::from_sec1_bytes(point).map_err(|_| Error::Crypto)?;
+            let sig = Signature::from_scalars(
+                p521::FieldBytes::clone_from_slice(&scalar::<66>(&r)?),
+                p521::FieldBytes::clone_from_slice(&scalar::<66>(&s)?),
+            ).map_err(|_| Error::Crypto)?;
+            key.verify(data, &sig).map_err(|_| Error::Crypto)
+        },
+    }
+}
+
+fn scalar<const N: usize>(int: &MPInt) -> Result<[u8; N], Error> {
+    let bytes = int.as_positive_bytes().ok_or(Error::Crypto)?;
+    let pad   = N.checked_sub(bytes.len()).ok_or(Error::Crypto)?;
+
+    let mut scalar = [0u8; N];
+    scalar[pad..].copy_from_slice(bytes);
+    Ok(scalar)
+}
+
+impl Decode for SshSig {
+    type Error = Error;
+
+    fn decode(reader: &mut impl Reader) -> Result<Self, Error> {
+        let mut magic = [0u8; MAGIC.len()];
+        reader.read(&mut magic)?;
+
+        if magic != MAGIC {
+            return Err(Error::FormatEncoding);
+        }
+
+        let version = u32::decode(reader)?;
+        if version != VERSION {
+            return Err(Error::Version { number: version });
+        }
+
+        let public_key = reader.read_prefixed(KeyData::decode)?;
+        let namespace  = String::decode(reader)?;
+        let reserved   = Vec::decode(reader)?;
+        let hash_alg   = HashAlg::decode(reader)?;
+
+        if namespace.is_empty() {
+            return Err(Error::Namespace);
+        }
+
+        let (algorithm, signature, sk) = reader.read_prefixed(|reader| {
+            let algorithm = Algorithm::decode(reader)?;
+            let signature = Vec::decode(reader)?;
+            let sk        = match algorithm {
+                Algorithm::SkEcdsaSha2NistP256 | Algorithm::SkEd25519 => Some(Sk {
+                    flags:   u8::decode(reader)?,
+                    counter: u32::decode(reader)?,
+                }),
+                _ => None,
+            };
+
+            match reader.is_finished() {
+                true  => Ok((algorithm, signature, sk)),
+                false => Err(Error::FormatEncoding),
+            }
+        })?;
+
+        Ok(Self { public_key, namespace, reserved, hash_alg, algorithm, signature, sk })
+    }
+}
+
+impl Encode for SshSig {
+    type Error = Error;
+
+    fn encoded_len(&self) -> Result<usize, Error> {
+        Ok(MAGIC.len()
+            + VERSION.encoded_len()?
+            + self.public_key.encoded_len_prefixed()?
+            + self.namespace.encoded_len()?
+            + self.reserved.encoded_len()?
+            + self.hash_alg.encoded_len()?
+            + 4 + self.signature_len()?)
+    }
+
+    fn encode(&self, writer: &mut impl Writer) -> Result<(), Error> {
+        writer.write(MAGIC)?;
+        VERSION.encode(writer)?;
+        self.public_key.encode_prefixed(writer)?;
+        self.namespace.encode(writer)?;
+        self.reserved.encode(writer)?;
+        self.hash_alg.encode(writer)?;
+
+        self.signature_len()?.encode(writer)?;
+        self.algorithm.encode(writer)?;
+        self.signature.encode(writer)?;
+        if let Some(sk) = self.sk {
+            sk.flags.encode(writer)?;
+            sk.counter.encode(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl PemLabel for SshSig {
+    const PEM_LABEL: &'static str = "SSH SIGNATURE";
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+    use ssh_encoding::{Encode, EncodePem};
+    use ssh_key::{EcdsaCurve, Error, LineEnding, MPInt};
+    use super::{ecdsa, SshSig, Sk};
+
+    const MSG: &[u8] = b"signet test vector\n";
+    const NS:  &str  = "file";
+
+    const RSA: &str = "\
+-----BEGIN SSH SIGNATURE-----\n\
+U1NIU0lHAAAAAQAAARcAAAAHc3NoLXJzYQAAAAMBAAEAAAEBALFUqLdUTm6nGG7FS9bNr8\n\
+7YoiDVTrqd1BXR/EkNmGrTLUJxkLPaQvuk2osC+2Z1k8mEiih4FuONrPVXoGerj4FW4CZi\n\
+V3RGMF7xPInkgMDEWd3Un/ejNSOGFU7FkbYWWEle37YxZGVxOuynAoBs47q6ffXkhsNuGI\n\
+Hbcq7xwIgjeoyO4maRmA6NInfn4vdghUOvbmBI1WRTwzPH32UGg/paGbmqr8T4pDTYw/L/\n\
+bRyJoiWwxKZsBxYxWjiBpWjOa2EAdNRIl6cIEXDFRb3hE3MDfziK9A1j29M2xg1f3WNT2r\n\
+8YYqCaOHEMol44f9418lzliF86p7/urI6mwrNW+TMAAAAEZmlsZQAAAAAAAAAGc2hhNTEy\n\
+AAABFAAAAAxyc2Etc2hhMi01MTIAAAEAE7gn8ofJ4fm+WQiFskvWIvfMpmBMSZVbFo2TAG\n\
+qp1g+yJBEce5q5W8JFHCtCKMRjka3HwpJgLndZwsBEjkNEdMBKX8yHibAivxWH8OH1qzEj\n\
+jxGTJ7R0oB+igHAPwjDedJ0jZfm/5wJuywJGFgpnvcCwDgZiCXRIFdfrSeHhu6hSNs6ymP\n\
+Q9U50e2Z5W/zn6VUNrrTfvYPDLoGbxPvxTy35lflugrUHd1E8qW3MRtN2CzY4K6NVrmCI4\n\
+RPGUzE3385aHdrFzUTPa7eawYN7sUWOUN8XziTy6O/xbVzj8FoN4zUQPw/tPWt1Ig3fx4H\n\
+uPvvPzrwz1fEWz+7EGeyeeAw==\n\
+-----END SSH SIGNATURE-----";
+
+    const P256: &str = "\
+-----BEGIN SSH SIGNATURE-----\n\
+U1NIU0lHAAAAAQAAAGgAAAATZWNkc2Etc2hhMi1uaXN0cDI1NgAAAAhuaXN0cDI1NgAAAE\n\
+EEwXEigXThG6xSelmlQu4AyOdZNc+KRfnnZKSPUhPHjI9gvi0UzFnKjNeblAFwvn1gAwLY\n\
+2DtqRtGnSdV+vo/zagAAAARmaWxlAAAAAAAAAAZzaGE1MTIAAABjAAAAE2VjZHNhLXNoYT\n\
+ItbmlzdHAyNTYAAABIAAAAIH0yYW5zsi5b93wAN1FSm6rUV2dJ7DqB0wLNFOec66ydAAAA\n\
+IDS1y+uqilNmJqJHdvYvFAozS/ZOkAJHKJ448svQM9y1\n\
+-----END SSH SIGNATURE-----";
+
+    const P384: &str = "\
+-----BEGIN SSH SIGNATURE-----\n\
+U1NIU0lHAAAAAQAAAIgAAAATZWNkc2Etc2hhMi1uaXN0cDM4NAAAAAhuaXN0cDM4NAAAAG\n\
+EERh/EpERrQRthotScriCbbXjlMM46KV4iJQjkKfX0Qm/OdA40DxbHNed6v5gAKJN6VBEg\n\
+gp2VwmB+TX4qBNBux9y+RE/GaaiQ8XX44Chk8W2nKlad9tQFDkItbAviRuGBAAAABGZpbG\n\
+UAAAAAAAAABnNoYTUxMgAAAIQAAAATZWNkc2Etc2hhMi1uaXN0cDM4NAAAAGkAAAAxAKO6\n\
+M3nX5IzrkmYzqsnRmPqX96X0EiNF8JyVCRtPCLuoNpv+cZv7D5sqbRC/ZSucXwAAADBEDo\n\
+k/l6Iq0HtJ81Tui0/08sFUjhmZ+s5IqJsKYQJ4nJInvVoeDaF6Szrfi87A5vE=\n\
+-----END SSH SIGNATURE-----";
+
+    const P521: &str = "\
+-----BEGIN SSH SIGNATURE-----\n\
+U1NIU0lHAAAAAQAAAKwAAAATZWNkc2Etc2hhMi1uaXN0cDUyMQAAAAhuaXN0cDUyMQAAAI\n\
+UEAHv/V9X3H2kP7WApPo5aAhpqu217psL96G4zMKj0lfemSi1+YIaY0B5GQw/Xw5kOlUki\n\
+ElSXipja7KkwMAIAfkJIAUo1wO4snWdY2nr3qfDV2WQ2pz81boghad1dmCi54S7y+o593W\n\
+W0O8MJe+IhwAIPK/qBeouCvqvU/bfZ5NHoLxAUAAAABGZpbGUAAAAAAAAABnNoYTUxMgAA\n\
+AKcAAAATZWNkc2Etc2hhMi1uaXN0cDUyMQAAAIwAAABCAe0vC5EDeRQbDR2/U23v3QkY9W\n\
+mHnhLGhecefFiDOTykNndo6+1C7XNdl2SQ4P2uOKQMILNR+81SfsEgLEGXAqWxAAAAQgEz\n\
+tpFxOTUPv9k6a53uh9fb8PZbw2n/cTZC49TKgNKlHpz3NRlReKo1ipHmhplZ0jsw5Gaj0v\n\
+Ul3cchXJM/gLdZ0A==\n\
+-----END SSH SIGNATURE-----";
+
+    const ED25519: &str = "\
+-----BEGIN SSH SIGNATURE-----\n\
+U1NIU0lHAAAAAQAAADMAAAALc3NoLWVkMjU1MTkAAAAgbvei/W41MYibkeH1I/71Ce+cS1\n\
+9Yoh6ZCBqoN1DeCiwAAAAEZmlsZQAAAAAAAAAGc2hhNTEyAAAAUwAAAAtzc2gtZWQyNTUx\n\
+OQAAAEA+C14rV2Xgq3XnaF8+q4CeLfm36X5OJVg4NCw812CeT6bEXuXMAcaVaCPbEHo89S\n\
+McdUXjGzDLdqly1mANPg8D\n\
+-----END SSH SIGNATURE-----";
+
+    const SK_ECDSA: &str = "\
+-----BEGIN SSH SIGNATURE-----\n\
+U1NIU0lHAAAAAQAAAH8AAAAic2stZWNkc2Etc2hhMi1uaXN0cDI1NkBvcGVuc3NoLmNvbQ\n\
+AAAAhuaXN0cDI1NgAAAEEEI7MOKMALnTFv02FgGAu6h8CUNT/Dg/iKBRycEUw9ByI86cw6\n\
+u6Pf6NuQLyuiwyNZwq7mtZZ6qOIMFoTQhbcPugAAAARzc2g6AAAABGZpbGUAAAAAAAAABn\n\
+NoYTUxMgAAAHgAAAAic2stZWNkc2Etc2hhMi1uaXN0cDI1NkBvcGVuc3NoLmNvbQAAAEkA\n\
+AAAgL9eZj0EhwVaSQaXtFzs1RkeeBuOKQ3aSUUg/IvuGCRgAAAAhALpuyoz8GDeOpr+DRG\n\
+m9L9wLhoNj0GTcmySKqnFBlXuFAQAAAAc=\n\
+-----END SSH SIGNATURE-----";
+
+    const SK_ED25519: &str = "\
+-----BEGIN SSH SIGNATURE-----\n\
+U1NIU0lHAAAAAQAAAEoAAAAac2stc3NoLWVkMjU1MTlAb3BlbnNzaC5jb20AAAAg3wbRxP\n\
+IV02IC9WfdGGDx777ZqqocCH/swB4zMazwaKgAAAAEc3NoOgAAAARmaWxlAAAAAAAAAAZz\n\
+aGE1MTIAAABnAAAAGnNrLXNzaC1lZDI1NTE5QG9wZW5zc2guY29tAAAAQJayOAxvDIjB38\n\
+kzkSFW+OtyKmjXAEykjgoPCqs9PpDs3wahwVKA/JoSAvVk+9tJTi7FgPUqihIIMV9MFteD\n\
+pwMFAAAAKg==\n\
+-----END SSH SIGNATURE-----";
+
+    const SK_NO_TOUCH: &str = "\
+-----BEGIN SSH SIGNATURE-----\n\
+U1NIU0lHAAAAAQAAAH8AAAAic2stZWNkc2Etc2hhMi1uaXN0cDI1NkBvcGVuc3NoLmNvbQ\n\
+AAAAhuaXN0cDI1NgAAAEEE0VKDhiSrVOCljBCQ14+6isOfgh7uh7kAYMGep4+cMsmj5GZk\n\
+jpi7cBAyt2Dx6rcCv5RHi8zcg6MOzWG68RaVoQAAAARzc2g6AAAABGZpbGUAAAAAAAAABn\n\
+NoYTUxMgAAAHkAAAAic2stZWNkc2Etc2hhMi1uaXN0cDI1NkBvcGVuc3NoLmNvbQAAAEoA\n\
+AAAhANWmQCWuXHuYMln15IXEbYb0NgnBhA/K9BExlmsxXHoRAAAAIQDUutd7RKHNvmEMKj\n\
+UVkrB0XDxgawzwoHBiZlZy7yNGEwAAAAAJ\n\
+-----END SSH SIGNATURE-----";
+
+    const VECTORS: [&str; 8] = [RSA, P256, P384, P521, ED25519, SK_ECDSA, SK_ED25519, SK_NO_TOUCH];
+
+    fn sig(pem: &str) -> SshSig {
+        SshSig::from_pem(pem).unwrap()
+    }
+
+    #[test]
+    fn verify() {
+        for pem in VECTORS {
+            let sig = sig(pem);
+            sig.verify(NS, MSG).unwrap();
+            assert_eq!(sig.encode_pem_string(LineEnding::LF).unwrap().trim_end(), pem);
+        }
+    }
+
+    #[test]
+    fn tampered_message() {
+        for pem in VECTORS {
+            let result = sig(pem).verify(NS, b"signet test vector\r\n");
+            assert!(matches!(result, Err(Error::Crypto)));
+        }
+    }
+
+    #[test]
+    fn wrong_namespace() {
+        for pem in VECTORS {
+            let result = sig(pem).verify("git", MSG);
+            assert!(matches!(result, Err(Error::Namespace)));
+        }
+    }
+
+    #[test]
+    fn trailing_garbage() {
+        for pem in VECTORS {
+            let mut sig = sig(pem);
+            sig.signature.push(0);
+            assert!(sig.verify(NS, MSG).is_err());
+        }
+
+        let mut sig = sig(P256);
+        sig.sk = Some(Sk { flags: Sk::PRESENCE, counter: 0 });
+        let pem = sig.encode_pem_string(LineEnding::LF).unwrap();
+        assert!(SshSig::from_pem(pem).is_err());
+    }
+
+    #[test]
+    fn security_key() {
+        let sk = sig(SK_ECDSA).sk().unwrap();
+        assert_eq!((sk.flags, sk.counter), (0x01, 7));
+        assert!(sk.presence() && !sk.verification());
+
+        let sk = sig(SK_ED25519).sk().unwrap();
+        assert_eq!((sk.flags, sk.counter), (0x05, 42));
+        assert!(sk.presence() && sk.verification());
+
+        let sk = sig(SK_NO_TOUCH).sk().unwrap();
+        assert_eq!((sk.flags, sk.counter), (0x00, 9));
+        assert!(!sk.presence() && !sk.verification());
+
+        assert!(sig(P256).sk().is_none());
+    }
+
+    #[test]
+    fn security_key_tampered() {
+        for pem in [SK_ECDSA, SK_ED25519] {
+            let sk = sig(pem).sk().unwrap();
+            for tampered in [Sk { flags: sk.flags ^ Sk::VERIFICATION, ..sk }, Sk { counter: sk.counter + 1, ..sk }] {
+                let mut sig = sig(pem);
+                sig.sk = Some(tampered);
+                assert!(matches!(sig.verify(NS, MSG), Err(Error::Crypto)));
+            }
+        }
+    }
+
+    #[test]
+    fn p521_scalars() {
+        use p521::ecdsa::{Signature, SigningKey, VerifyingKey};
+        use p521::ecdsa::signature::Signer;
+
+        let key   = SigningKey::random(&mut OsRng);
+        let point = VerifyingKey::from(&key).to_encoded_point(false);
+        let sig: Signature = key.sign(MSG);
+        let (r, s) = sig.split_bytes();
+
+        let blob = |r: &[u8], s: &[u8]| {
+            let mut blob = Vec::new();
+            for int in [r, s] {
+                let zeros = int.iter().take_while(|byte| **byte == 0).count();
+                MPInt::from_positive_bytes(&int[zeros..]).unwrap().encode(&mut blob).unwrap();
+            }
+            blob
+        };
+
+        let order = hex::decode("01fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffa51868783bf2f966b7fcc0148f709a5d03bb5c9b8899c47aebb6fb71e91386409").unwrap();
+        let curve = EcdsaCurve::NistP521;
+
+        ecdsa(curve, point.as_bytes(), MSG, &blob(&r, &s)).unwrap();
+        assert!(ecdsa(curve, point.as_bytes(), b"signet", &blob(&r, &s)).is_err());
+        assert!(ecdsa(curve, point.as_bytes(), MSG, &blob(&s, &r)).is_err());
+        assert!(ecdsa(curve, point.as_bytes(), MSG, &blob(&[], &s)).is_err());
+        assert!(ecdsa(curve, point.as_bytes(), MSG, &blob(&r, &order)).is_err());
+        assert!(ecdsa(curve, &point.as_bytes()[..66], MSG, &blob(&r, &s)).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use ssh_key::{HashAlg, LineEnding, PrivateKey, PublicKey};
+use time::OffsetDateTime;
+use crate::sshsig::SshSig;
+use crate::timestamp;
+
+pub const NAMESPACE: &str = "signet-succession";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Succession {
+    pub old:        PublicKey,
+    pub new:        PublicKey,
+    pub date:       String,
+    pub signatures: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Unsigned(String),
+    Invalid(ssh_key::Error),
+}
+
+impl Succession {
+    pub fn sign(old: &PrivateKey, new: &PrivateKey, date: OffsetDateTime) -> Result<Self, Error> {
+        let mut succession = Self {
+            old:        old.public_key().clone(),
+            new:        new.public_key().clone(),
+            date:       timestamp::compact(date),
+            signatures: Vec::new(),
+        };
+
+        let msg = succession.message();
+        for key in [old, new] {
+            let sig = key.sign(NAMESPACE, HashAlg::Sha512, &msg)?;
+            succession.signatures.push(sig.to_pem(LineEnding::LF)?);
+        }
+
+        Ok(succession)
+    }
+
+    pub fn verify(&self) -> Result<(), Error> {
+        let msg  = self.message();
+        let sigs = self.signatures.iter().map(|pem| {
+            SshSig::from_pem(pem)
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        for key in [&self.old, &self.new] {
+            let sig = sigs.iter().find(|sig| sig.public_key() == key.key_data());
+            match sig {
+                Some(sig) => sig.verify(NAMESPACE, &msg)?,
+                None      => {
+                    let fp = key.fingerprint(HashAlg::Sha256);
+                    return Err(Error::Unsigned(fp.to_string()));
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn succeeds(&self, key: &PublicKey) -> bool {
+        self.new.key_data() == key.key_data()
+    }
+
+    fn message(&self) -> Vec<u8> {
+        let old = self.old.fingerprint(HashAlg::Sha256);
+        let new = self.new.fingerprint(HashAlg::Sha256);
+        format!("old {old}\nnew {new}\ndate {}\n", self.date).into_bytes()
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unsigned(fp)   => write!(f, "succession statement not signed by {fp}"),
+            Self::Invalid(error) => write!(f, "invalid succession statement: {error}"),
+        }
+    }
+}
+
+impl From<ssh_key::Error> for Error {
+    fn from(error: ssh_key::Error) -> Self {
+        Self::Invalid(error)
+    }
+}
@@ -6,6 +6,7 @@ pub trait System {
     fn sync(path: &Path, data: &[u8])  -> Result<()>;
 
     fn mkdir(path: &Path)              -> Result<()>;
+    fn list(path: &Path)               -> Result<Vec<PathBuf>>;
     fn read(input: &Input)             -> Result<Vec<u8>>;
     fn write(path: &Path, data: &[u8]) -> Result<()>;
 
@@ -0,0 +1,92 @@
+use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+use time::macros::format_description;
+
+pub fn now() -> OffsetDateTime {
+    OffsetDateTime::now_utc()
+}
+
+pub fn parse(str: &str) -> Option<OffsetDateTime> {
+    let (str, utc) = match strip(str, "Z").or_else(|| strip(str, "UTC")) {
+        Some(str) => (str, true),
+        None      => (str, false),
+    };
+
+    if !matches!(str.len(), 8 | 12 | 14) || !str.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let field = |range: std::ops::Range<usize>| str.get(range)?.parse::<u16>().ok();
+
+    let year   = field(0..4)?;
+    let month  = Month::try_from(field(4..6)? as u8).ok()?;
+    let day    = field(6..8)? as u8;
+    let hour   = field(8..10).unwrap_or(0) as u8;
+    let minute = field(10..12).unwrap_or(0) as u8;
+    let second = field(12..14).unwrap_or(0) as u8;
+
+    let date = Date::from_calendar_date(year.into(), month, day).ok()?;
+    let time = Time::from_hms(hour, minute, second).ok()?;
+    let time = PrimitiveDateTime::new(date, time);
+
+    Some(match utc {
+        true  => time.assume_utc(),
+        false => time.assume_offset(local(time.assume_utc())),
+    })
+}
+
+pub fn format(time: OffsetDateTime) -> String {
+    let format = format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]");
+    let time   = time.to_offset(local(time));
+    time.format(format).unwrap_or_default()
+}
+
+pub fn compact(time: OffsetDateTime) -> String {
+    let format = format_description!("[year][month][day][hour][minute][second]Z");
+    let time   = time.to_offset(UtcOffset::UTC);
+    time.format(format).unwrap_or_default()
+}
+
+pub fn expiry(str: &str, from: OffsetDateTime) -> Option<OffsetDateTime> {
+    let split = str.find(|c: char| !c.is_ascii_digit()).filter(|n| *n > 0);
+    let (count, unit) = match split {
+        Some(n) => str.split_at(n),
+        None    => return parse(str),
+    };
+
+    let count = count.parse::<i64>().ok()?;
+    let span  = match unit {
+        "s" => Duration::seconds(count),
+        "m" => Duration::minutes(count),
+        "h" => Duration::hours(count),
+        "d" => Duration::days(count),
+        "w" => Duration::weeks(count),
+        "y" => Duration::days(count.checked_mul(365)?),
+        _   => return parse(str),
+    };
+
+    from.checked_add(span)
+}
+
+pub fn span(duration: Duration) -> String {
+    let plural = |n: i64, unit: &str| match n {
+        1 => format!("1 {unit}"),
+        n => format!("{n} {unit}s"),
+    };
+
+    match duration.abs() {
+        d if d.whole_days()    > 0 => plural(d.whole_days(), "day"),
+        d if d.whole_hours()   > 0 => plural(d.whole_hours(), "hour"),
+        d if d.whole_minutes() > 0 => plural(d.whole_minutes(), "minute"),
+        d                          => plural(d.whole_seconds(), "second"),
+    }
+}
+
+fn local(time: OffsetDateTime) -> UtcOffset {
+    UtcOffset::local_offset_at(time).unwrap_or(UtcOffset::UTC)
+}
+
+fn strip<'a>(str: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = str.len().checked_sub(suffix.len()).filter(|n| *n > 0)?;
+    let (head, tail) = (str.get(..split)?, str.get(split..)?);
+    tail.eq_ignore_ascii_case(suffix).then_some(head)
+}
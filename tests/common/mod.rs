@@ -0,0 +1,9 @@
+use tempfile::TempDir;
+use signet::{signet, Password, Signet, System};
+
+pub fn store() -> (Signet<impl System>, TempDir) {
+    let root   = TempDir::new().unwrap();
+    let signet = signet(root.path().to_owned());
+    signet.initialize(Password::insecure("test")).unwrap();
+    (signet, root)
+}
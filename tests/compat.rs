@@ -0,0 +1,236 @@
+#![allow(clippy::redundant_field_names)]
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use rand_core::OsRng;
+use ssh_key::{Algorithm, HashAlg, LineEnding, PrivateKey};
+use tempfile::TempDir;
+
+const SK_NO_TOUCH: &str = "\
+-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAAH8AAAAic2stZWNkc2Etc2hhMi1uaXN0cDI1NkBvcGVuc3NoLmNvbQ
+AAAAhuaXN0cDI1NgAAAEEE0VKDhiSrVOCljBCQ14+6isOfgh7uh7kAYMGep4+cMsmj5GZk
+jpi7cBAyt2Dx6rcCv5RHi8zcg6MOzWG68RaVoQAAAARzc2g6AAAABGZpbGUAAAAAAAAABn
+NoYTUxMgAAAHkAAAAic2stZWNkc2Etc2hhMi1uaXN0cDI1NkBvcGVuc3NoLmNvbQAAAEoA
+AAAhANWmQCWuXHuYMln15IXEbYb0NgnBhA/K9BExlmsxXHoRAAAAIQDUutd7RKHNvmEMKj
+UVkrB0XDxgawzwoHBiZlZy7yNGEwAAAAAJ
+-----END SSH SIGNATURE-----
+";
+
+struct Output {
+    code:   i32,
+    stdout: String,
+    stderr: String,
+}
+
+struct Fixture {
+    dir: TempDir,
+    key: String,
+    fp:  String,
+}
+
+impl Fixture {
+    fn new(options: &str) -> Self {
+        let dir = TempDir::new().unwrap();
+        let key = PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap();
+        let sig = key.sign("file", HashAlg::Sha512, b"data\n").unwrap();
+
+        let public = key.public_key().to_openssh().unwrap();
+        let line   = format!("alice@example.com,bob@example.com {options} {public}\n");
+        fs::write(dir.path().join("allowed_signers"), line).unwrap();
+        fs::write(dir.path().join("data.sig"), sig.to_pem(LineEnding::LF).unwrap()).unwrap();
+
+        let fp = key.public_key().fingerprint(HashAlg::Sha256).to_string();
+        Self { dir: dir, key: public, fp: fp }
+    }
+
+    fn run(&self, args: &[&str], stdin: &str) -> Output {
+        signet(self.dir.path(), args, stdin)
+    }
+}
+
+fn signet(dir: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_signet"))
+        .current_dir(dir)
+        .arg("-S").arg(dir.join("store"))
+        .arg("-Y").args(args)
+        .env("RUST_BACKTRACE", "0")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    let output = child.wait_with_output().unwrap();
+
+    Output {
+        code:   output.status.code().unwrap(),
+        stdout: String::from_utf8(output.stdout).unwrap(),
+        stderr: String::from_utf8(output.stderr).unwrap(),
+    }
+}
+
+#[test]
+fn verify() {
+    let fixture = Fixture::new("namespaces=\"file\"");
+    let verify  = |identity, ns, data, opts: &[&str]| {
+        let args = ["verify", "-f", "allowed_signers", "-I", identity, "-n", ns, "-s", "data.sig"];
+        fixture.run(&[&args[..], opts].concat(), data)
+    };
+
+    let good = verify("alice@example.com", "file", "data\n", &[]);
+    assert_eq!(good.code, 0);
+    assert_eq!(good.stdout, format!("Good \"file\" signature for alice@example.com with ED25519 key {}\n", fixture.fp));
+    assert_eq!(good.stderr, "");
+
+    let pubkey = verify("bob@example.com", "file", "data\n", &["-O", "print-pubkey"]);
+    assert_eq!(pubkey.code, 0);
+    assert_eq!(pubkey.stdout, format!("Good \"file\" signature for bob@example.com with ED25519 key {}\n{}\n", fixture.fp, fixture.key));
+
+    let unknown = verify("carol@example.com", "file", "data\n", &[]);
+    assert_eq!(unknown.code, 255);
+    assert_eq!(unknown.stdout, "Could not verify signature.\n");
+    assert_eq!(unknown.stderr, "");
+
+    let tampered = verify("alice@example.com", "file", "tampered\n", &[]);
+    assert_eq!(tampered.code, 255);
+    assert_eq!(tampered.stdout, "Could not verify signature.\n");
+    assert_eq!(tampered.stderr, "Signature verification failed: incorrect signature\n");
+
+    let namespace = verify("alice@example.com", "git", "data\n", &[]);
+    assert_eq!(namespace.code, 255);
+    assert_eq!(namespace.stdout, "Could not verify signature.\n");
+    assert_eq!(namespace.stderr, "Couldn't verify signature: namespace does not match\n");
+
+    let invalid = verify("alice@example.com", "file", "data\n", &["-O", "bogus"]);
+    assert_eq!(invalid.code, 1);
+    assert_eq!(invalid.stdout, "");
+    assert_eq!(invalid.stderr, "Invalid option \"bogus\"\n");
+}
+
+#[test]
+fn verify_time() {
+    let fixture = Fixture::new("valid-after=\"20300101Z\",valid-before=\"20310101Z\"");
+    let verify  = |opts: &[&str]| {
+        let args = ["verify", "-f", "allowed_signers", "-I", "alice@example.com", "-n", "file", "-s", "data.sig"];
+        fixture.run(&[&args[..], opts].concat(), "data\n")
+    };
+
+    let now = verify(&[]);
+    assert_eq!(now.code, 255);
+    assert_eq!(now.stdout, "Could not verify signature.\n");
+    assert!(now.stderr.starts_with("allowed_signers:1: key is not yet valid: verify time "), "{}", now.stderr);
+
+    let valid = verify(&["-O", "verify-time=20300601"]);
+    assert_eq!(valid.code, 0);
+    assert_eq!(valid.stderr, "");
+
+    let expired = verify(&["-O", "verify-time=20310102"]);
+    assert_eq!(expired.code, 255);
+    assert!(expired.stderr.starts_with("allowed_signers:1: key has expired: verify time "), "{}", expired.stderr);
+
+    let invalid = verify(&["-O", "verify-time=2030"]);
+    assert_eq!(invalid.code, 1);
+    assert_eq!(invalid.stderr, "Invalid \"verify-time\" option\n");
+}
+
+#[test]
+fn no_touch_required() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("data.sig"), SK_NO_TOUCH).unwrap();
+
+    let data  = "signet test vector\n";
+    let check = signet(dir.path(), &["check-novalidate", "-n", "file", "-s", "data.sig", "-O", "print-pubkey"], data);
+    assert_eq!(check.code, 0);
+
+    let key = check.stdout.lines().nth(1).unwrap();
+    fs::write(dir.path().join("allowed_signers"), format!("alice@example.com {key}\n")).unwrap();
+
+    let args = ["verify", "-f", "allowed_signers", "-I", "alice@example.com", "-n", "file", "-s", "data.sig"];
+
+    let touch = signet(dir.path(), &args, data);
+    assert_eq!(touch.code, 255);
+    assert_eq!(touch.stdout, "Could not verify signature.\n");
+    assert_eq!(touch.stderr, "Signature lacks user presence\n");
+
+    let none = signet(dir.path(), &[&args[..], &["-O", "no-touch-required"]].concat(), data);
+    assert_eq!(none.code, 0);
+    assert!(none.stdout.starts_with("Good \"file\" signature for alice@example.com with ECDSA-SK key SHA256:"));
+    assert_eq!(none.stderr, "");
+}
+
+#[test]
+fn find_principals() {
+    let fixture = Fixture::new("namespaces=\"file\"");
+
+    let found = fixture.run(&["find-principals", "-f", "allowed_signers", "-s", "data.sig"], "");
+    assert_eq!(found.code, 0);
+    assert_eq!(found.stdout, "alice@example.com\nbob@example.com\n");
+    assert_eq!(found.stderr, "");
+
+    let other = Fixture::new("");
+    fs::copy(other.dir.path().join("data.sig"), fixture.dir.path().join("other.sig")).unwrap();
+
+    let missing = fixture.run(&["find-principals", "-f", "allowed_signers", "-s", "other.sig"], "");
+    assert_eq!(missing.code, 255);
+    assert_eq!(missing.stdout, "");
+    assert_eq!(missing.stderr, "No principal matched.\n");
+
+    let invalid = fixture.run(&["find-principals", "-f", "allowed_signers", "-s", "data.sig", "-O", "print-pubkey"], "");
+    assert_eq!(invalid.code, 1);
+    assert_eq!(invalid.stderr, "Invalid option \"print-pubkey\"\n");
+}
+
+#[test]
+fn match_principals() {
+    let fixture = Fixture::new("");
+
+    let found = fixture.run(&["match-principals", "-f", "allowed_signers", "-I", "bob@example.com"], "");
+    assert_eq!(found.code, 0);
+    assert_eq!(found.stdout, "alice@example.com,bob@example.com\n");
+    assert_eq!(found.stderr, "");
+
+    let missing = fixture.run(&["match-principals", "-f", "allowed_signers", "-I", "carol@example.com"], "");
+    assert_eq!(missing.code, 255);
+    assert_eq!(missing.stdout, "");
+    assert_eq!(missing.stderr, "No principal matched.\n");
+
+    let invalid = fixture.run(&["match-principals", "-f", "allowed_signers", "-I", "bob@example.com", "-O", "verify-time=20300101"], "");
+    assert_eq!(invalid.code, 1);
+    assert_eq!(invalid.stderr, "Invalid option \"verify-time=20300101\"\n");
+}
+
+#[test]
+fn check_novalidate() {
+    let fixture = Fixture::new("");
+    let check   = |ns, data, opts: &[&str]| {
+        let args = ["check-novalidate", "-n", ns, "-s", "data.sig"];
+        fixture.run(&[&args[..], opts].concat(), data)
+    };
+
+    let good = check("file", "data\n", &[]);
+    assert_eq!(good.code, 0);
+    assert_eq!(good.stdout, format!("Good \"file\" signature with ED25519 key {}\n", fixture.fp));
+    assert_eq!(good.stderr, "");
+
+    let pubkey = check("file", "data\n", &["-O", "print-pubkey"]);
+    assert_eq!(pubkey.code, 0);
+    assert_eq!(pubkey.stdout, format!("Good \"file\" signature with ED25519 key {}\n{}\n", fixture.fp, fixture.key));
+
+    let namespace = check("git", "data\n", &[]);
+    assert_eq!(namespace.code, 255);
+    assert_eq!(namespace.stdout, "Could not verify signature.\n");
+    assert_eq!(namespace.stderr, "Couldn't verify signature: namespace does not match\n");
+
+    let tampered = check("file", "tampered\n", &[]);
+    assert_eq!(tampered.code, 255);
+    assert_eq!(tampered.stderr, "Signature verification failed: incorrect signature\n");
+
+    let invalid = check("file", "data\n", &["-O", "no-touch-required"]);
+    assert_eq!(invalid.code, 1);
+    assert_eq!(invalid.stdout, "");
+    assert_eq!(invalid.stderr, "Invalid option \"no-touch-required\"\n");
+}
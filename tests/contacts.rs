@@ -0,0 +1,145 @@
+#![allow(clippy::redundant_field_names)]
+
+mod common;
+
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use rand_core::OsRng;
+use ssh_key::{Algorithm, HashAlg, PrivateKey};
+use signet::Trust;
+use signet::args::{Add, Contacts as Cmd, Import};
+use signet::command::contacts;
+use common::store;
+
+struct Server {
+    url:  String,
+    body: Arc<Mutex<String>>,
+}
+
+impl Server {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url      = format!("http://{}/keys", listener.local_addr().unwrap());
+        let body     = Arc::new(Mutex::new(String::new()));
+        let served   = body.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut request = Vec::<u8>::new();
+                let mut buffer  = [0u8; 1024];
+                while !request.windows(4).any(|end| end == b"\r\n\r\n") {
+                    match stream.read(&mut buffer) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n)          => request.extend(&buffer[..n]),
+                    }
+                }
+                let body = served.lock().unwrap().clone();
+                let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(body.as_bytes());
+            }
+        });
+
+        Self { url, body }
+    }
+
+    fn serve(&self, keys: &[&str]) {
+        *self.body.lock().unwrap() = keys.join("\n");
+    }
+}
+
+fn key() -> (String, String) {
+    let key = PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap();
+    let key = key.public_key();
+    (key.to_openssh().unwrap(), hex::encode(key.fingerprint(HashAlg::Sha256)))
+}
+
+fn import(url: &str, trust: Trust) -> Cmd {
+    Cmd::Import(Import {
+        source:     url.to_owned(),
+        principals: vec!["alice@example.com".to_owned()],
+        note:       String::new(),
+        trust:      trust,
+    })
+}
+
+#[test]
+fn import_and_refresh() {
+    let server = Server::start();
+    let (signet, _dir) = store();
+
+    let (one, id1)   = key();
+    let (two, id2)   = key();
+    let (three, id3) = key();
+
+    server.serve(&[&one, &two]);
+    contacts(&signet, import(&server.url, Trust::Full)).unwrap();
+
+    let list = signet.contacts().unwrap();
+    assert!(list.contains(&id1) && list.contains(&id2));
+
+    server.serve(&[&two, &three]);
+    contacts(&signet, Cmd::Refresh).unwrap();
+
+    let list = signet.contacts().unwrap();
+    assert!(!list.contains(&id1));
+    assert!(list.contains(&id2) && list.contains(&id3));
+}
+
+#[test]
+fn oversized_response() {
+    let server = Server::start();
+    let (signet, _dir) = store();
+
+    let (one, id1) = key();
+    let (two, id2) = key();
+
+    server.serve(&[&one, &two]);
+    contacts(&signet, import(&server.url, Trust::Full)).unwrap();
+
+    let padding = "#".repeat(1 << 20);
+    server.serve(&[&one, &padding]);
+    assert!(contacts(&signet, Cmd::Refresh).is_err());
+    assert!(contacts(&signet, import(&server.url, Trust::Full)).is_err());
+
+    let list = signet.contacts().unwrap();
+    assert!(list.contains(&id1) && list.contains(&id2));
+}
+
+#[test]
+fn import_keeps_existing_contacts() {
+    let server = Server::start();
+    let (signet, dir) = store();
+    let root = dir.path();
+
+    let (one, id1) = key();
+    let path = root.join("one.pub");
+    fs::write(&path, &one).unwrap();
+
+    contacts(&signet, Cmd::Add(Add {
+        key:        path.into(),
+        principals: vec!["mallory@example.com".to_owned()],
+        note:       String::new(),
+        trust:      Trust::Never,
+    })).unwrap();
+
+    server.serve(&[&one]);
+    contacts(&signet, import(&server.url, Trust::Full)).unwrap();
+
+    let list    = signet.contacts().unwrap();
+    let contact = list.find(&id1).unwrap();
+    assert_eq!(contact.trust, Trust::Never);
+    assert_eq!(contact.source, None);
+}
+
+#[test]
+fn refuse_plain_http() {
+    let (signet, _dir) = store();
+
+    let result = contacts(&signet, import("http://example.com/keys", Trust::Full));
+    assert!(result.unwrap_err().to_string().contains("refusing"));
+}
@@ -0,0 +1,70 @@
+#![allow(clippy::redundant_field_names)]
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use ssh_key::{Algorithm, HashAlg, SshSig};
+use signet::{Input, Signet, System};
+use signet::args::{Describe, Keys, Sign};
+use signet::command::{keys, sign, Format};
+use common::store;
+
+fn keygen(root: &Path, kind: &str, bits: &str) -> PathBuf {
+    let path   = root.join(format!("{kind}-{bits}"));
+    let status = Command::new("ssh-keygen")
+        .args(["-q", "-t", kind, "-b", bits, "-N", "", "-f"])
+        .arg(&path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    path
+}
+
+fn import(signet: &Signet<impl System>, path: &Path) -> anyhow::Result<()> {
+    keys(signet, Keys::Import(Input::File(path.to_owned()), Describe {
+        label:      None,
+        comment:    None,
+        principals: None,
+    }), Format::Text)
+}
+
+#[test]
+fn refuse_weak_keys() {
+    let (signet, dir) = store();
+    let root = dir.path();
+
+    for (kind, bits, error) in [("dsa", "1024", "refusing weak DSA key"), ("rsa", "1024", "refusing weak RSA-1024 key")] {
+        let path = keygen(root, kind, bits);
+        assert_eq!(import(&signet, &path).unwrap_err().to_string(), error);
+    }
+
+    assert_eq!(signet.current().unwrap().keys().count(), 0);
+}
+
+#[test]
+fn rsa_sha2_512() {
+    let (signet, dir) = store();
+    let root = dir.path();
+
+    import(&signet, &keygen(root, "rsa", "2048")).unwrap();
+    let id = signet.current().unwrap().keys().next().unwrap().id();
+
+    let data = root.join("data");
+    fs::write(&data, "data").unwrap();
+
+    for hash in [HashAlg::Sha256, HashAlg::Sha512] {
+        sign(&signet, Sign {
+            key:    id.clone(),
+            ns:     "file".to_owned(),
+            hash:   hash,
+            append: false,
+            data:   data.clone().into(),
+        }, Format::Text).unwrap();
+
+        let sig = SshSig::from_pem(fs::read(root.join("data.sig")).unwrap()).unwrap();
+        assert_eq!(sig.algorithm(), Algorithm::Rsa { hash: Some(HashAlg::Sha512) });
+        assert_eq!(sig.hash_alg(), hash);
+    }
+}
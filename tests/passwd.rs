@@ -0,0 +1,131 @@
+#![allow(clippy::redundant_field_names)]
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use signet::{Input, Key, KeyType, Password, Signet, System};
+use signet::args::{Create, Describe, Keys, Passwd};
+use signet::command::{keys, passwd, Format};
+
+thread_local! {
+    static PROMPTS: RefCell<VecDeque<&'static str>> = RefCell::default();
+    static FAIL:    Cell<bool>                      = Cell::default();
+}
+
+struct Scripted;
+
+impl System for Scripted {
+    fn init(path: &Path, data: &[u8]) -> Result<()> {
+        fs::write(path, data)
+    }
+
+    fn sync(path: &Path, data: &[u8]) -> Result<()> {
+        match FAIL.with(Cell::get) {
+            true  => Err(Error::other("disk full")),
+            false => fs::write(path, data),
+        }
+    }
+
+    fn mkdir(path: &Path) -> Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn list(path: &Path) -> Result<Vec<PathBuf>> {
+        fs::read_dir(path)?.map(|entry| Ok(entry?.path())).collect()
+    }
+
+    fn read(input: &Input) -> Result<Vec<u8>> {
+        match input {
+            Input::File(path) => fs::read(path),
+            Input::Stdin      => Ok(Vec::new()),
+        }
+    }
+
+    fn write(path: &Path, data: &[u8]) -> Result<()> {
+        fs::write(path, data)
+    }
+
+    fn prompt(_: &str) -> Result<String> {
+        let next = PROMPTS.with(|prompts| prompts.borrow_mut().pop_front());
+        next.map(str::to_owned).ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "no input"))
+    }
+}
+
+fn answer(prompts: &[&'static str]) {
+    PROMPTS.with(|queue| queue.borrow_mut().extend(prompts));
+}
+
+fn store() -> (Signet<Scripted>, TempDir) {
+    let dir    = TempDir::new().unwrap();
+    let signet = Signet::<Scripted>::new(dir.path().to_owned());
+    answer(&["old", "old"]);
+    signet.initialize(Password::create::<Scripted>(false).unwrap()).unwrap();
+
+    for _ in 0..2 {
+        answer(&["old"]);
+        keys(&signet, Keys::Create(Create {
+            kind:     KeyType::Ed25519,
+            after:    None,
+            expires:  None,
+            describe: Describe { label: None, comment: None, principals: None },
+        }), Format::Text).unwrap();
+    }
+
+    (signet, dir)
+}
+
+fn decrypts(signet: &Signet<Scripted>, password: &str) -> bool {
+    signet.current().unwrap().keys().all(|key| {
+        let Key::SSH(key) = key;
+        key.decrypt(password).is_ok()
+    })
+}
+
+fn chain(dir: &TempDir) -> String {
+    fs::read_to_string(dir.path().join("chains/default.yml")).unwrap()
+}
+
+#[test]
+fn reencrypt() {
+    let (signet, _dir) = store();
+    assert!(decrypts(&signet, "old"));
+
+    answer(&["old", "new", "new"]);
+    passwd(&signet, Passwd { secret: None }, Format::Text).unwrap();
+
+    assert!(decrypts(&signet, "new"));
+    assert!(!decrypts(&signet, "old"));
+
+    let keychain = signet.current().unwrap();
+    answer(&["old"]);
+    assert_eq!(keychain.password().lookup::<Scripted>().unwrap_err().to_string(), "incorrect password");
+    answer(&["new"]);
+    assert_eq!(keychain.password().lookup::<Scripted>().unwrap().as_str(), "new");
+}
+
+#[test]
+fn rollback() {
+    let (signet, dir) = store();
+    let before = chain(&dir);
+
+    answer(&["wrong"]);
+    let error = passwd(&signet, Passwd { secret: None }, Format::Text).unwrap_err();
+    assert_eq!(error.to_string(), "incorrect password");
+    assert_eq!(chain(&dir), before);
+
+    answer(&["old", "new", "other"]);
+    let error = passwd(&signet, Passwd { secret: None }, Format::Text).unwrap_err();
+    assert_eq!(error.to_string(), "password mismatch");
+    assert_eq!(chain(&dir), before);
+
+    FAIL.with(|fail| fail.set(true));
+    answer(&["old", "new", "new"]);
+    assert!(passwd(&signet, Passwd { secret: None }, Format::Text).is_err());
+    FAIL.with(|fail| fail.set(false));
+
+    assert_eq!(chain(&dir), before);
+    assert!(decrypts(&signet, "old"));
+}
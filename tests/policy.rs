@@ -0,0 +1,55 @@
+#![allow(clippy::redundant_field_names)]
+
+use rand_core::OsRng;
+use ssh_key::{Algorithm, HashAlg, PrivateKey};
+use signet::{Policy, Signature};
+
+fn policy(key: &str) -> Result<Policy, serde_yaml::Error> {
+    serde_yaml::from_str(&format!("
+version: 1
+default: deny
+rules:
+  - name: firmware
+    keys: [{key}]
+"))
+}
+
+#[test]
+fn exact_keys() {
+    let key = PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap();
+    let key = key.public_key();
+    let fp  = key.fingerprint(HashAlg::Sha256);
+    let id  = hex::encode(fp);
+
+    let sig = Signature {
+        key:        key,
+        principals: &[],
+        namespace:  "file",
+        path:       None,
+        sk:         None,
+    };
+
+    for entry in [fp.to_string(), id.clone(), id.to_uppercase()] {
+        let policy   = policy(&entry).unwrap();
+        let verdicts = policy.evaluate(&sig).unwrap();
+        assert!(verdicts.iter().all(|(_, verdict)| verdict.passed()));
+    }
+
+    let other    = PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap();
+    let other    = hex::encode(other.public_key().fingerprint(HashAlg::Sha256));
+    let policy   = policy(&other).unwrap();
+    let verdicts = policy.evaluate(&sig).unwrap();
+    assert!(!verdicts.iter().any(|(_, verdict)| verdict.passed()));
+}
+
+#[test]
+fn reject_short_keys() {
+    let key = PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap();
+    let id  = hex::encode(key.public_key().fingerprint(HashAlg::Sha256));
+    let fp  = key.public_key().fingerprint(HashAlg::Sha256).to_string();
+
+    assert!(policy(&id[..8]).is_err());
+    assert!(policy(&id[..63]).is_err());
+    assert!(policy(&fp[..20]).is_err());
+    assert!(policy("''").is_err());
+}
@@ -0,0 +1,96 @@
+#![allow(clippy::redundant_field_names)]
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use rand_core::OsRng;
+use ssh_key::{Algorithm, HashAlg, LineEnding, PrivateKey};
+use time::OffsetDateTime;
+use signet::{Signet, Succession, System, Trust};
+use signet::args::{Add, Contacts as Cmd, Verify};
+use signet::command::{contacts, verify, Format};
+use common::store;
+
+fn add(signet: &Signet<impl System>, root: &Path, key: &PrivateKey) {
+    let path = root.join(format!("{}.pub", hex::encode(key.public_key().fingerprint(HashAlg::Sha256))));
+    fs::write(&path, key.public_key().to_openssh().unwrap()).unwrap();
+    contacts(signet, Cmd::Add(Add {
+        key:        path.into(),
+        principals: vec!["alice@example.com".to_owned()],
+        note:       String::new(),
+        trust:      Trust::Full,
+    })).unwrap();
+}
+
+fn rotate(signet: &Signet<impl System>, root: &Path, old: &PrivateKey, new: &PrivateKey) {
+    let path       = root.join("succession.yml");
+    let succession = Succession::sign(old, new, OffsetDateTime::now_utc()).unwrap();
+    fs::write(&path, serde_yaml::to_string(&succession).unwrap()).unwrap();
+    contacts(signet, Cmd::Succession(path.into())).unwrap();
+}
+
+#[test]
+fn follow_every_succession() {
+    let (signet, dir) = store();
+    let root = dir.path();
+
+    let [one, two, new] = [(); 3].map(|_| PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap());
+
+    add(&signet, root, &one);
+    add(&signet, root, &two);
+    rotate(&signet, root, &one, &new);
+    rotate(&signet, root, &two, &new);
+
+    let id = hex::encode(one.public_key().fingerprint(HashAlg::Sha256));
+    contacts(&signet, Cmd::Delete(id)).unwrap();
+
+    let data = root.join("data");
+    let sig  = root.join("data.sig");
+    fs::write(&data, "data").unwrap();
+    let pem = new.sign("file", HashAlg::Sha512, b"data").unwrap().to_pem(LineEnding::LF).unwrap();
+    fs::write(&sig, pem).unwrap();
+
+    verify(&signet, Verify {
+        sig:       sig,
+        ns:        "file".to_owned(),
+        revoked:   None,
+        threshold: None,
+        signers:   None,
+        time:      None,
+        data:      data.into(),
+    }, Format::Text).unwrap();
+}
+
+#[test]
+fn revoked_predecessor() {
+    let (signet, dir) = store();
+    let root = dir.path();
+
+    let [old, new] = [(); 2].map(|_| PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap());
+
+    add(&signet, root, &old);
+    rotate(&signet, root, &old, &new);
+
+    let data    = root.join("data");
+    let sig     = root.join("data.sig");
+    let revoked = root.join("revoked");
+    fs::write(&data, "data").unwrap();
+    let pem = new.sign("file", HashAlg::Sha512, b"data").unwrap().to_pem(LineEnding::LF).unwrap();
+    fs::write(&sig, pem).unwrap();
+    fs::write(&revoked, old.public_key().to_openssh().unwrap()).unwrap();
+
+    let args = |revoked: Option<PathBuf>| Verify {
+        sig:       sig.clone(),
+        ns:        "file".to_owned(),
+        revoked:   revoked,
+        threshold: None,
+        signers:   None,
+        time:      None,
+        data:      data.clone().into(),
+    };
+
+    verify(&signet, args(None), Format::Text).unwrap();
+    let error = verify(&signet, args(Some(revoked)), Format::Text).unwrap_err();
+    assert_eq!(error.to_string(), "key is revoked");
+}
@@ -0,0 +1,189 @@
+#![allow(clippy::redundant_field_names)]
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use rand_core::OsRng;
+use ssh_key::{Algorithm, HashAlg, LineEnding, PrivateKey};
+use signet::{Key, KeyType, Signet, System, Trust};
+use signet::args::{Add, Contacts as Cmd, Countersign, Create, Describe, Keys, Verify};
+use signet::command::{contacts, keys, verify, Format};
+use common::store;
+
+fn contact(signet: &Signet<impl System>, root: &Path, key: &PrivateKey, principal: &str, trust: Trust) {
+    let path = root.join(format!("{}.pub", hex::encode(key.public_key().fingerprint(HashAlg::Sha256))));
+    fs::write(&path, key.public_key().to_openssh().unwrap()).unwrap();
+    contacts(signet, Cmd::Add(Add {
+        key:        path.into(),
+        principals: vec![principal.to_owned()],
+        note:       String::new(),
+        trust:      trust,
+    })).unwrap();
+}
+
+fn create(signet: &Signet<impl System>, after: Option<&str>, expires: Option<&str>) -> anyhow::Result<()> {
+    keys(signet, Keys::Create(Create {
+        kind:     KeyType::Ed25519,
+        after:    after.map(str::to_owned),
+        expires:  expires.map(str::to_owned),
+        describe: Describe { label: None, comment: None, principals: None },
+    }), Format::Text)
+}
+
+fn signed(root: &Path, keys: &[&PrivateKey]) -> (PathBuf, PathBuf) {
+    let data = root.join("data");
+    let sig  = root.join("data.sig");
+    fs::write(&data, "data").unwrap();
+    let pem = keys.iter().map(|key| {
+        key.sign("file", HashAlg::Sha512, b"data").unwrap().to_pem(LineEnding::LF).unwrap()
+    }).collect::<String>();
+    fs::write(&sig, pem).unwrap();
+    (data, sig)
+}
+
+fn random() -> PrivateKey {
+    PrivateKey::random(OsRng, Algorithm::Ed25519).unwrap()
+}
+
+fn own(signet: &Signet<impl System>) -> String {
+    create(signet, None, None).unwrap();
+    let keychain = signet.current().unwrap();
+    keychain.keys().last().unwrap().id()
+}
+
+fn args(data: &Path, sig: &Path, time: Option<&str>) -> Verify {
+    Verify {
+        sig:       sig.to_owned(),
+        ns:        "file".to_owned(),
+        revoked:   None,
+        threshold: None,
+        signers:   None,
+        time:      time.map(str::to_owned),
+        data:      data.to_owned().into(),
+    }
+}
+
+#[test]
+fn verify_time() {
+    let (signet, dir) = store();
+    let root = dir.path();
+
+    let alice = random();
+    contact(&signet, root, &alice, "alice@example.com", Trust::Full);
+
+    let (data, sig) = signed(root, &[&alice]);
+    verify(&signet, args(&data, &sig, Some("20200101")), Format::Text).unwrap();
+
+    create(&signet, Some("20300101"), Some("20310101")).unwrap();
+    let Key::SSH(own) = signet.current().unwrap().keys().next().unwrap().clone();
+    let own = own.decrypt("test").unwrap();
+
+    let (data, sig) = signed(root, &[&own]);
+    verify(&signet, args(&data, &sig, Some("20300601")), Format::Text).unwrap();
+    assert!(verify(&signet, args(&data, &sig, Some("20200101")), Format::Text).is_err());
+    assert!(verify(&signet, args(&data, &sig, Some("20310101")), Format::Text).is_err());
+}
+
+#[test]
+fn reject_inverted_window() {
+    let (signet, _dir) = store();
+
+    let error = create(&signet, Some("20300101"), Some("20200101")).unwrap_err();
+    assert!(error.to_string().contains("is not after valid-after"));
+    assert!(create(&signet, Some("20300101"), Some("20300101")).is_err());
+    assert_eq!(signet.current().unwrap().keys().count(), 0);
+}
+
+#[test]
+fn threshold() {
+    let (signet, dir) = store();
+    let root = dir.path();
+
+    let [alice, bob, carol, dave] = [(); 4].map(|_| random());
+    contact(&signet, root, &alice, "alice@example.com", Trust::Full);
+    contact(&signet, root, &bob, "bob@example.com", Trust::Full);
+    contact(&signet, root, &carol, "carol@example.com", Trust::Marginal);
+    contact(&signet, root, &dave, "dave@example.com", Trust::Full);
+
+    fs::write(root.join("policy.yml"), "
+version: 1
+groups:
+  release: [alice@example.com, bob@example.com, carol@example.com]
+").unwrap();
+
+    let threshold = |keys: &[&PrivateKey], required: usize, signers: Option<&str>| {
+        let (data, sig) = signed(root, keys);
+        verify(&signet, Verify {
+            threshold: Some(required),
+            signers:   signers.map(str::to_owned),
+            ..args(&data, &sig, None)
+        }, Format::Text)
+    };
+
+    threshold(&[&alice, &bob], 2, None).unwrap();
+    threshold(&[&alice, &bob, &dave], 3, None).unwrap();
+    threshold(&[&alice, &bob], 2, Some("release")).unwrap();
+    threshold(&[&alice, &carol], 1, Some("release")).unwrap();
+
+    let failed = [
+        (threshold(&[&alice, &alice], 2, None),               "only 1 of 2 required signatures"),
+        (threshold(&[&alice, &carol], 2, None),               "only 1 of 2 required signatures"),
+        (threshold(&[&alice, &carol], 2, Some("release")),    "only 1 of 2 required signatures"),
+        (threshold(&[&alice, &dave], 2, Some("release")),     "only 1 of 2 required signatures"),
+        (threshold(&[&carol], 1, None),                       "only 0 of 1 required signatures"),
+        (threshold(&[&alice, &bob], 3, None),                 "threshold must be between 1 and 2"),
+        (threshold(&[&alice, &bob], 0, None),                 "threshold must be between 1 and 2"),
+        (threshold(&[&alice, &bob], 2, Some("maintainers")),  "unknown policy group"),
+    ];
+
+    for (result, error) in failed {
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains(error), "{message}");
+    }
+}
+
+#[test]
+fn countersignatures() {
+    let (signet, dir) = store();
+    let root = dir.path();
+
+    let alice = random();
+    contact(&signet, root, &alice, "alice@example.com", Trust::Full);
+    let [one, two] = [(); 2].map(|_| own(&signet));
+
+    let (data, sig) = signed(root, &[&alice]);
+    verify(&signet, args(&data, &sig, None), Format::Text).unwrap();
+
+    let countersign = |key: &str, sig: PathBuf| {
+        signet::command::countersign(&signet, Countersign {
+            key:  key.to_owned(),
+            hash: HashAlg::Sha512,
+            sig:  sig,
+        })
+    };
+
+    let csig = root.join("data.sig.csig");
+    countersign(&one, sig.clone()).unwrap();
+    countersign(&two, sig.clone()).unwrap();
+    countersign(&one, csig.clone()).unwrap();
+    assert_eq!(fs::read_to_string(&csig).unwrap().matches("BEGIN SSH SIGNATURE").count(), 2);
+    verify(&signet, args(&data, &sig, None), Format::Text).unwrap();
+
+    let level = root.join("data.sig.csig.csig");
+    fs::copy(&csig, &level).unwrap();
+    let error = verify(&signet, args(&data, &sig, None), Format::Text).unwrap_err();
+    assert!(error.to_string().contains("incorrect signature"), "{error}");
+
+    let mallory = random();
+    let msg     = fs::read_to_string(&csig).unwrap();
+    let pem     = mallory.sign("file", HashAlg::Sha512, msg.as_bytes()).unwrap();
+    fs::write(&level, pem.to_pem(LineEnding::LF).unwrap()).unwrap();
+    let error = verify(&signet, args(&data, &sig, None), Format::Text).unwrap_err();
+    assert!(error.to_string().contains("data.sig.csig.csig"), "{error}");
+
+    fs::remove_file(&level).unwrap();
+    fs::write(&sig, fs::read_to_string(&sig).unwrap() + &fs::read_to_string(&csig).unwrap()).unwrap();
+    assert!(verify(&signet, args(&data, &sig, None), Format::Text).is_err());
+}
+